@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+
+use crate::dialect::Dialect;
+
+/// 按 GitHub 的规则为标题生成锚点，重复的标题依次追加 -1、-2……
+#[derive(Default)]
+pub struct Slugger {
+    seen: HashMap<String, usize>,
+    /// GitLab 把连续的 - 合并为一个
+    squeeze: bool,
+}
+
+impl Slugger {
+    pub fn new(dialect: Dialect) -> Self {
+        Slugger { seen: HashMap::new(), squeeze: dialect == Dialect::Gitlab }
+    }
+
+    pub fn slug(&mut self, heading: &str) -> String {
+        let mut base = slugify(heading);
+        if self.squeeze {
+            base = squeeze_hyphens(&base);
+        }
+        let mut slug = base.clone();
+        loop {
+            match self.seen.get_mut(&slug) {
+                None => break,
+                Some(count) => {
+                    *count += 1;
+                    slug = format!("{}-{}", base, count);
+                }
+            }
+        }
+        self.seen.insert(slug.clone(), 0);
+        slug
+    }
+}
+
+/// 小写化，去掉除字母数字、`-`、`_` 之外的字符，空格转为 `-`
+pub fn slugify(heading: &str) -> String {
+    heading
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            '-' | '_' => Some(c),
+            c if c.is_alphanumeric() => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+fn squeeze_hyphens(slug: &str) -> String {
+    let mut out = String::with_capacity(slug.len());
+    for c in slug.chars() {
+        if !(c == '-' && out.ends_with('-')) {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_punctuation_and_keeps_cjk() {
+        assert_eq!(slugify("File: src/main.rs"), "file-srcmainrs");
+        assert_eq!(slugify("  What's new? (v1.2) "), "whats-new-v12");
+        assert_eq!(slugify("文件：项目 代码_说明"), "文件项目-代码_说明");
+        assert_eq!(slugify("a - b"), "a---b");
+    }
+
+    #[test]
+    fn duplicates_get_numbered_suffixes() {
+        let mut slugger = Slugger::new(Dialect::Gfm);
+        let slugs: Vec<String> = ["Intro", "intro", "Intro-1", "INTRO"].iter().map(|h| slugger.slug(h)).collect();
+        assert_eq!(slugs, ["intro", "intro-1", "intro-1-1", "intro-2"]);
+    }
+
+    #[test]
+    fn gitlab_squeezes_hyphens() {
+        let mut slugger = Slugger::new(Dialect::Gitlab);
+        assert_eq!(slugger.slug("a - b"), "a-b");
+        assert_eq!(slugger.slug("a  b"), "a-b-1");
+    }
+}
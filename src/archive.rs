@@ -0,0 +1,77 @@
+use flate2::read::GzDecoder;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// 解压后的压缩包；TempDir 被丢弃时临时目录随之删除
+pub struct Extracted {
+    pub dir: TempDir,
+    /// 实际的项目根目录：压缩包只含一个顶层目录时为该目录
+    pub root: PathBuf,
+}
+
+/// 按扩展名判断是否为支持的压缩包
+pub fn is_archive(path: &Path) -> bool {
+    path.is_file() && kind(path).is_some()
+}
+
+/// 去掉压缩包扩展名后的路径，`/a/project.tar.gz` -> `/a/project`，用于推导项目名与默认输出位置
+pub fn stem_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let lower = name.to_ascii_lowercase();
+    let stem_len = [".tar.gz", ".tgz", ".zip"]
+        .iter()
+        .find(|ext| lower.ends_with(*ext))
+        .map_or(name.len(), |ext| name.len() - ext.len());
+    path.with_file_name(&name[..stem_len])
+}
+
+enum Kind {
+    Zip,
+    TarGz,
+}
+
+fn kind(path: &Path) -> Option<Kind> {
+    let name = path.file_name()?.to_string_lossy().to_ascii_lowercase();
+    if name.ends_with(".zip") {
+        Some(Kind::Zip)
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        Some(Kind::TarGz)
+    } else {
+        None
+    }
+}
+
+/// 解压到临时目录。两种格式都会拒绝 `..` 与绝对路径，条目不会落到临时目录之外
+pub fn extract(path: &Path) -> io::Result<Extracted> {
+    let dir = tempfile::Builder::new().prefix("code2md-").tempdir()?;
+    let file = File::open(path)?;
+    match kind(path) {
+        Some(Kind::Zip) => {
+            let mut zip = zip::ZipArchive::new(file).map_err(invalid(path))?;
+            zip.extract(dir.path()).map_err(invalid(path))?;
+        }
+        Some(Kind::TarGz) => {
+            tar::Archive::new(GzDecoder::new(file)).unpack(dir.path())?;
+        }
+        None => return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("不支持的压缩包：{}", path.display()))),
+    }
+
+    let root = single_top_dir(dir.path())?.unwrap_or_else(|| dir.path().to_path_buf());
+    Ok(Extracted { root: root.canonicalize()?, dir })
+}
+
+/// GitHub 等导出的压缩包通常把所有文件放在 `project-main/` 之下，此时以它为根目录
+fn single_top_dir(dir: &Path) -> io::Result<Option<PathBuf>> {
+    let mut entries = fs::read_dir(dir)?.collect::<io::Result<Vec<_>>>()?;
+    if entries.len() != 1 {
+        return Ok(None);
+    }
+    let entry = entries.remove(0);
+    Ok(entry.file_type()?.is_dir().then(|| entry.path()))
+}
+
+fn invalid(path: &Path) -> impl Fn(zip::result::ZipError) -> io::Error + '_ {
+    move |e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), e))
+}
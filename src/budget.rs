@@ -0,0 +1,124 @@
+use std::collections::HashSet;
+
+use crate::render::FileEntry;
+use crate::tokens;
+
+/// 截断后剩余不到这个数的 token 时整个省略，只留占位说明
+const MIN_KEPT_TOKENS: usize = 64;
+
+/// 为截断说明行及逐行计数的误差预留的 token 数
+const MARKER_TOKENS: usize = 24;
+
+/// 清单与入口文件，超出预算时也保持完整
+const KEEP_WHOLE: &[&str] = &[
+    // 清单
+    "cargo.toml", "package.json", "pyproject.toml", "setup.py", "setup.cfg", "requirements.txt",
+    "go.mod", "pom.xml", "build.gradle", "build.gradle.kts", "settings.gradle", "settings.gradle.kts",
+    "gemfile", "composer.json", "cmakelists.txt", "makefile", "dockerfile", "readme.md",
+    // 入口
+    "main.rs", "lib.rs", "main.py", "__main__.py", "app.py", "main.go", "main.c", "main.cpp",
+    "index.js", "index.ts", "main.js", "main.ts", "program.cs", "main.java", "main.kt",
+];
+
+/// 路径（以 / 开头）中出现这些片段的文件看起来是生成的，最先被截断
+const GENERATED_MARKERS: &[&str] = &[
+    ".min.", ".generated.", ".g.", "_pb2.", ".pb.", "/generated/", "/gen/", ".designer.", "/bundle.",
+];
+
+/// 被截断或省略的文件
+pub struct Trimmed {
+    pub path: String,
+    /// false 为截断（保留开头），true 为整个省略
+    pub dropped: bool,
+}
+
+/// 文件内容的 token 总数超过 budget 时，按优先级截断或省略文件，直到不超过预算：
+/// 先处理看起来是生成的文件，再处理其余文件，同类中从大到小；清单与入口文件保持完整，
+/// --dedup 时被其他文件引用的原文件也保持完整，否则引用处的内容随之丢失。
+/// 截断处与被省略的文件都在文档中注明
+pub fn fit(entries: &mut [FileEntry], budget: usize) -> Vec<Trimmed> {
+    tokens::count_entries(entries);
+    let mut total = tokens::total(entries);
+    let mut trimmed = Vec::new();
+    if total <= budget {
+        return trimmed;
+    }
+
+    let referenced: HashSet<String> = entries.iter().filter_map(|e| e.duplicate_of.clone()).collect();
+    let mut order: Vec<usize> = (0..entries.len())
+        .filter(|&i| entries[i].tokens.unwrap_or(0) > 0 && !is_kept_whole(&entries[i]))
+        .filter(|&i| !referenced.contains(&entries[i].display_path()))
+        .collect();
+    order.sort_by_key(|&i| (!looks_generated(&entries[i]), std::cmp::Reverse(entries[i].tokens)));
+
+    for i in order {
+        if total <= budget { break; }
+        let entry = &mut entries[i];
+        let tokens = entry.tokens.unwrap_or(0);
+        let target = tokens.saturating_sub(total - budget);
+        let dropped = target < MIN_KEPT_TOKENS + MARKER_TOKENS;
+        if dropped {
+            entry.placeholder = Some(format!("Omitted to fit the token budget ({} tokens)", tokens));
+            entry.content.clear();
+            entry.tokens = Some(0);
+        } else {
+            entry.content = truncate(&entry.content, target - MARKER_TOKENS);
+            entry.tokens = Some(tokens::count(&entry.content));
+        }
+        total = total - tokens + entry.tokens.unwrap_or(0);
+        trimmed.push(Trimmed { path: entry.display_path(), dropped });
+    }
+    trimmed
+}
+
+/// 保留开头若干行，使其不超过 target 个 token，末尾注明省略的行数
+fn truncate(content: &str, target: usize) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut used = 0;
+    let mut kept = 0;
+    for line in &lines {
+        let cost = tokens::count(line) + 1;
+        if used + cost > target { break; }
+        used += cost;
+        kept += 1;
+    }
+    let mut out = lines[..kept].join("\n");
+    out.push_str(&format!("\n... {} lines omitted to fit the token budget ...\n", lines.len() - kept));
+    out
+}
+
+fn file_name(entry: &FileEntry) -> String {
+    entry.rel_path.rsplit('/').next().unwrap_or("").to_lowercase()
+}
+
+fn is_kept_whole(entry: &FileEntry) -> bool {
+    KEEP_WHOLE.contains(&file_name(entry).as_str())
+}
+
+fn looks_generated(entry: &FileEntry) -> bool {
+    let path = format!("/{}", entry.rel_path.to_lowercase());
+    GENERATED_MARKERS.iter().any(|m| path.contains(m))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{convert, ConversionOptions};
+    use std::fs;
+
+    #[test]
+    fn dedup_originals_are_not_trimmed() {
+        let dir = tempfile::tempdir().unwrap();
+        let shared: String = (0..400).map(|i| format!("shared line {}\n", i)).collect();
+        let other: String = (0..400).map(|i| format!("other line {}\n", i)).collect();
+        fs::write(dir.path().join("a.txt"), &shared).unwrap();
+        fs::write(dir.path().join("b.txt"), &shared).unwrap();
+        fs::write(dir.path().join("c.txt"), &other).unwrap();
+
+        let options = ConversionOptions { capture: true, dedup: true, budget: Some(3000), ..Default::default() };
+        let report = convert(dir.path(), &options).unwrap();
+        let document = report.document.unwrap();
+        assert!(!report.truncated.contains(&"a.txt".to_string()) && !report.omitted.contains(&"a.txt".to_string()));
+        assert!(report.truncated.contains(&"c.txt".to_string()) || report.omitted.contains(&"c.txt".to_string()));
+        assert!(document.contains("shared line 399\n"));
+    }
+}
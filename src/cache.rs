@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::SystemTime;
+
+/// 缓存文件名，位于项目目录下；与输出文件一样不会被收录
+pub const CACHE_FILE_NAME: &str = ".code2md-cache";
+
+/// 缓存格式的版本，格式变化时递增，旧缓存随之失效
+const VERSION: u32 = 1;
+
+/// 上次运行处理过的文件：大小与修改时间都未变时直接沿用处理后的内容，不再读取
+#[derive(Serialize, Deserialize, Default)]
+pub struct Cache {
+    version: u32,
+    /// 影响处理结果的选项（遮盖、去注释等），与本次不同时整个缓存作废
+    settings: String,
+    /// 以显示路径为键
+    files: HashMap<String, CachedFile>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CachedFile {
+    pub size: u64,
+    /// 取不到修改时间的文件不会写入缓存
+    pub modified: Option<SystemTime>,
+    pub language: String,
+    /// 遮盖、去注释等处理之后的内容
+    pub content: String,
+    /// 其中已遮盖的疑似密钥处数
+    pub redacted: usize,
+}
+
+impl Cache {
+    pub fn new(settings: String) -> Cache {
+        Cache { version: VERSION, settings, files: HashMap::new() }
+    }
+
+    /// 读取缓存；不存在、无法解析或选项不同时返回空缓存
+    pub fn load(path: &Path, settings: &str) -> Cache {
+        let cache = fs::read(path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<Cache>(&bytes).ok())
+            .filter(|c| c.version == VERSION && c.settings == settings);
+        match cache {
+            Some(cache) => {
+                tracing::debug!("读取缓存 {}（{} 个文件）", path.display(), cache.files.len());
+                cache
+            }
+            None => Cache::new(settings.to_string()),
+        }
+    }
+
+    /// 大小与修改时间都与记录一致时返回缓存的结果
+    pub fn get(&self, key: &str, size: u64, modified: SystemTime) -> Option<&CachedFile> {
+        self.files.get(key).filter(|f| f.size == size && f.modified == Some(modified))
+    }
+
+    pub fn insert(&mut self, key: String, file: CachedFile) {
+        self.files.insert(key, file);
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, serde_json::to_vec(self)?)
+    }
+}
@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::extract;
+use crate::job::Job;
+use crate::progress::Progress;
+use crate::{ConversionOptions, Error, Format, Result};
+
+/// 已提交的文档与按当前源码重新生成的结果的比较
+pub struct CheckReport {
+    /// 被比较的文档
+    pub output: PathBuf,
+    /// 忽略 front matter 与换行符差异后两者完全一致
+    pub up_to_date: bool,
+    /// 两边都有但内容不同的文件
+    pub changed: Vec<String>,
+    /// 源码中有、文档中没有的文件
+    pub added: Vec<String>,
+    /// 文档中有、源码中已没有的文件
+    pub removed: Vec<String>,
+}
+
+/// 在内存中重新生成文档，与输出位置上已有的文档比较，不写任何文件。
+/// 逐文件的差异只对 Markdown 文档给出，其他格式只比较整体
+pub fn check(paths: &[PathBuf], options: &ConversionOptions) -> Result<CheckReport> {
+    // 与 convert 相同的 Job 决定输出位置与要排除的文档自身；缓存不在检查时写入
+    let options = ConversionOptions { cache: false, progress: false, ..options.clone() };
+    let job = Job::new(paths, &options, Progress::new(false))?;
+    let Some(output) = job.output_path.clone() else {
+        return Err(Error::InvalidOptions("check 需要比较的文档，不能写到标准输出".into()));
+    };
+    let committed = fs::read_to_string(&output).map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => Error::PathNotFound(output.clone()),
+        _ => Error::Io(e),
+    })?;
+
+    let capture = ConversionOptions { capture: true, output: None, save_inside: false, manifest: false, ..options };
+    let generated = crate::run(&job, &capture)?.document.unwrap_or_default();
+
+    let committed = normalize(&committed);
+    let generated = normalize(&generated);
+    let mut report = CheckReport {
+        output,
+        up_to_date: committed == generated,
+        changed: Vec::new(),
+        added: Vec::new(),
+        removed: Vec::new(),
+    };
+    if report.up_to_date || job.format != Format::Markdown {
+        return Ok(report);
+    }
+
+    let old = files_of(&committed);
+    let new = files_of(&generated);
+    for (path, content) in &new {
+        match old.get(path) {
+            Some(previous) if previous == content => {}
+            Some(_) => report.changed.push(path.clone()),
+            None => report.added.push(path.clone()),
+        }
+    }
+    report.removed = old.keys().filter(|p| !new.contains_key(*p)).cloned().collect();
+    report.changed.sort();
+    report.added.sort();
+    report.removed.sort();
+    Ok(report)
+}
+
+/// 去掉每次都会变化的 front matter（含生成时间），并统一换行符
+fn normalize(document: &str) -> String {
+    let document = document.replace("\r\n", "\n");
+    let body = document
+        .strip_prefix("---\n")
+        .and_then(|rest| rest.split_once("\n---\n"))
+        .map_or(document.as_str(), |(_, body)| body);
+    body.trim_start_matches('\n').to_string()
+}
+
+fn files_of(markdown: &str) -> HashMap<String, String> {
+    extract::assemble(extract::parse(markdown))
+        .into_iter()
+        .map(|f| (f.path, f.content))
+        .collect()
+}
@@ -0,0 +1,544 @@
+use clap::{ArgAction, Args, Parser, Subcommand};
+use std::fs;
+use std::io::{self, Read};
+use std::path::PathBuf;
+
+use crate::completions::Shell;
+use code2xml::config::{parse_line_ranges, parse_size};
+use code2xml::{ConversionOptions, Dialect, Format, Locale, SortOrder};
+
+#[derive(Parser, Debug)]
+#[command(
+    version,
+    about = "将项目源码整理为单个 Markdown 文档",
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true,
+    arg_required_else_help = true
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    #[command(flatten)]
+    pub generate: GenerateArgs,
+
+    /// 没有可用的控制台时新开一个窗口，显示进度与结果
+    #[arg(long = "console", global = true)]
+    pub console: bool,
+
+    /// 显示更多日志：-v 显示被跳过的文件及原因，-vv 显示每个文件的处理过程
+    #[arg(short = 'v', long = "verbose", action = ArgAction::Count, global = true)]
+    pub verbose: u8,
+
+    /// 只显示错误：不显示进度条、警告与完成提示
+    #[arg(short = 'q', long = "quiet", global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// 生成 Markdown 文档（默认行为）
+    Generate(GenerateArgs),
+    /// 仅列出将被包含的文件
+    List(GenerateArgs),
+    /// 在内存中重新生成，检查已有的文档是否与源码一致；不一致时列出差异并以非零状态退出
+    Check(GenerateArgs),
+    /// 从生成的 Markdown 文档中还原源文件
+    Extract(ExtractArgs),
+    /// 逐条说明某个文件为何会或不会被收录
+    Explain(ExplainArgs),
+    /// 按 --manifest 写出的清单校验文档，内容不符时列出文件并以非零状态退出
+    Verify(VerifyArgs),
+    /// 生成 HTML 文档并在本机提供预览，源码变更时重新生成，浏览器中的页面自动刷新
+    Serve(ServeArgs),
+    /// 以 MCP 服务器运行（stdio），供 LLM 客户端按需获取项目文档、单个文件与目录树
+    Mcp(GenerateArgs),
+    /// 输出 shell 补全脚本
+    ///
+    /// 例如 code2xml completions bash > ~/.local/share/bash-completion/completions/code2xml
+    Completions {
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+    /// 打开拖放窗口（仅 Windows；不带参数双击运行时自动打开）
+    Gui,
+    /// 在资源管理器的文件夹右键菜单中添加“转换为 Markdown”（仅 Windows，当前用户）
+    InstallShell,
+    /// 移除 install-shell 添加的右键菜单
+    UninstallShell,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ExtractArgs {
+    /// 生成的 Markdown 文档，分卷时可依次给出多个
+    #[arg(required = true)]
+    pub inputs: Vec<PathBuf>,
+
+    /// 还原到的目录
+    #[arg(short = 'o', long = "output", value_name = "DIR", default_value = ".")]
+    pub output: PathBuf,
+
+    /// 覆盖已存在的文件
+    #[arg(short = 'f', long = "force")]
+    pub force: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct VerifyArgs {
+    /// 要校验的 Markdown 文档
+    pub document: PathBuf,
+
+    /// 清单文件（默认为文档旁的 <文档>.manifest.json）
+    #[arg(long = "manifest", value_name = "FILE")]
+    pub manifest: Option<PathBuf>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ServeArgs {
+    /// 监听的端口（只监听 127.0.0.1）
+    #[arg(long = "port", value_name = "PORT", default_value_t = 8000)]
+    pub port: u16,
+
+    #[command(flatten)]
+    pub generate: GenerateArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ExplainArgs {
+    /// 要检查的文件
+    pub file: PathBuf,
+
+    /// 文件所在的项目目录，可给出多个（默认为当前目录）
+    pub paths: Vec<PathBuf>,
+
+    /// 同 generate 的 --exclude
+    #[arg(short = 'e', long = "exclude", value_name = "GLOB")]
+    pub exclude: Vec<String>,
+
+    /// 同 generate 的 --include
+    #[arg(long = "include", value_name = "GLOB")]
+    pub include: Vec<String>,
+
+    /// 同 generate 的 --max-file-size
+    #[arg(long = "max-file-size", value_name = "SIZE", value_parser = parse_size)]
+    pub max_file_size: Option<u64>,
+
+    /// 同 generate 的 --max-depth
+    #[arg(long = "max-depth", value_name = "N")]
+    pub max_depth: Option<usize>,
+
+    /// 同 generate 的 --git-only
+    #[arg(long = "git-only")]
+    pub git_only: bool,
+
+    /// 同 generate 的 --since
+    #[arg(long = "since", value_name = "REF")]
+    pub since: Option<String>,
+
+    /// 同 generate 的 --include-md
+    #[arg(long = "include-md")]
+    pub include_md: bool,
+
+    /// 同 generate 的 --hidden
+    #[arg(long = "hidden")]
+    pub hidden: bool,
+
+    /// 同 generate 的 --extract-pdf
+    #[arg(long = "extract-pdf")]
+    pub extract_pdf: bool,
+
+    /// 同 generate 的 --skip-secrets
+    #[arg(long = "skip-secrets")]
+    pub skip_secrets: bool,
+}
+
+impl ExplainArgs {
+    pub fn options(&self) -> ConversionOptions {
+        ConversionOptions {
+            exclude: self.exclude.clone(),
+            include: self.include.clone(),
+            max_file_size: self.max_file_size,
+            max_depth: self.max_depth,
+            git_only: self.git_only,
+            since: self.since.clone(),
+            include_md: self.include_md,
+            hidden: self.hidden,
+            extract_pdf: self.extract_pdf,
+            skip_secrets: self.skip_secrets,
+            stdout: true,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct GenerateArgs {
+    /// 要转换的项目目录、压缩包（.zip、.tar.gz、.tgz）或 git 仓库地址，可给出多个，合并为一份文档；
+    /// 也可以是 "src/**/*.rs" 这样的通配模式，只收录匹配的文件
+    #[arg(required_unless_present = "files_from")]
+    pub paths: Vec<PathBuf>,
+
+    /// 只收录该列表中的文件，"-" 表示从标准输入读取；每行一个或以 NUL 分隔（fd -0、git ls-files -z）。
+    /// 不给出项目目录时以当前目录为项目目录
+    #[arg(long = "files-from", value_name = "PATH", conflicts_with_all = ["git_only", "since", "watch"])]
+    pub files_from: Option<PathBuf>,
+
+    /// 从 --files-from 读到的文件，由 read_files_from 填入
+    #[arg(skip)]
+    pub files: Option<Vec<PathBuf>>,
+
+    /// 将输出文件保存在项目目录内部（默认保存在其父目录）
+    #[arg(short = 'i', long = "inside")]
+    pub save_inside: bool,
+
+    /// 将文档写到标准输出而不是文件
+    #[arg(long = "stdout", conflicts_with = "save_inside")]
+    pub stdout: bool,
+
+    /// 不写输出文件，与 --clipboard 或 --stdout 搭配；单独使用时只输出 --tokens 等统计
+    #[arg(long = "no-file", conflicts_with_all = ["save_inside", "output", "name", "split_files", "manifest", "max_tokens", "max_output_size"])]
+    pub no_file: bool,
+
+    /// 输出文件路径，"-" 表示标准输出
+    #[arg(short = 'o', long = "output", value_name = "PATH", conflicts_with_all = ["save_inside", "stdout"])]
+    pub output: Option<PathBuf>,
+
+    /// 默认输出文件的文件名（不含目录，可省略扩展名），取代项目目录名；便于同一目录多次生成不同文档
+    #[arg(long = "name", value_name = "NAME", conflicts_with_all = ["output", "stdout"])]
+    pub name: Option<String>,
+
+    /// 文档标题（front matter、HTML、AsciiDoc 与分页索引的标题），取代项目目录名
+    #[arg(long = "title", value_name = "TEXT")]
+    pub title: Option<String>,
+
+    /// 文档中的路径相对于该目录显示（须包含扫描的目录），如扫描 monorepo/backend 时用 monorepo 显示 backend/src/...
+    #[arg(long = "relative-to", value_name = "DIR")]
+    pub relative_to: Option<PathBuf>,
+
+    /// 加在文档中每个路径前的前缀（在 --relative-to 得出的部分之前）
+    #[arg(long = "path-prefix", value_name = "PREFIX")]
+    pub path_prefix: Option<String>,
+
+    /// 排除匹配的路径（相对于项目目录的 glob，可重复）；"!" 开头的模式把已排除的路径重新收录
+    #[arg(short = 'e', long = "exclude", value_name = "GLOB")]
+    pub exclude: Vec<String>,
+
+    /// 输出格式
+    #[arg(short = 'f', long = "format", value_enum)]
+    pub format: Option<Format>,
+
+    /// 文件排列顺序（默认按路径）
+    #[arg(long = "sort", value_enum)]
+    pub sort: Option<SortOrder>,
+
+    /// 只收录被 git 跟踪的文件（使用 git ls-files）
+    #[arg(long = "git-only")]
+    pub git_only: bool,
+
+    /// 只收录相对于该 git 提交有改动的文件，如 main、HEAD~5
+    #[arg(long = "since", value_name = "REF")]
+    pub since: Option<String>,
+
+    /// 单个文件的大小上限，超过的文件被跳过；支持 256k、5M 等写法，默认 1M，0 表示不限
+    #[arg(long = "max-file-size", value_name = "SIZE", value_parser = parse_size)]
+    pub max_file_size: Option<u64>,
+
+    /// 跟随符号链接进入目录；同一文件只收录一次，链接成环时自动跳过
+    #[arg(long = "follow-symlinks")]
+    pub follow_symlinks: bool,
+
+    /// 收录所有 Markdown 文件（默认只收录 README.md；生成的文档本身仍被排除）
+    #[arg(long = "include-md")]
+    pub include_md: bool,
+
+    /// 进入 . 开头的目录，如 .cargo、.husky（.git、.idea、.vscode、.venv 等仍被忽略）
+    #[arg(long = "hidden")]
+    pub hidden: bool,
+
+    /// 收录 PDF（需求、设计文档等），提取其中的文字，在 Markdown 中以引用块输出；扫描件与加密文档无法提取。
+    /// PDF 的大小上限默认为 10 MiB，给出 --max-file-size 时以其为准
+    #[arg(long = "extract-pdf")]
+    pub extract_pdf: bool,
+
+    /// 不遮盖疑似密钥（私钥、访问令牌、password = "..." 等），默认会替换为 [REDACTED]
+    #[arg(long = "no-redact")]
+    pub no_redact: bool,
+
+    /// 完全跳过疑似密钥文件（.env、id_rsa、*.pem、密钥库及含私钥的文件），并在摘要中列出
+    #[arg(long = "skip-secrets")]
+    pub skip_secrets: bool,
+
+    /// 去掉源码中的注释以减少 token（按语言识别，不认识的语言保持原样）
+    #[arg(long = "strip-comments")]
+    pub strip_comments: bool,
+
+    /// 压缩空白：合并连续空行并去掉行尾空白
+    #[arg(long = "compact")]
+    pub compact: bool,
+
+    /// 只输出各文件的大纲：函数、类、结构体等定义所在的行（按 tree-sitter 语法识别）。
+    /// 支持 Rust、Python、JavaScript、TypeScript/TSX、Go、Java、C 与 C++，其他语言的文件照常输出完整内容
+    #[arg(long = "outline", conflicts_with = "line_numbers")]
+    pub outline: bool,
+
+    /// 把 CRLF 换行统一为 LF，使 Windows 与 Linux 上生成的文档一致；混用两者的文件会给出警告
+    #[arg(long = "normalize-eol")]
+    pub normalize_eol: bool,
+
+    /// 超过 N 行的文件只输出开头与结尾各 N/2 行，中间注明省略了多少行
+    #[arg(long = "max-lines-per-file", value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    pub max_lines_per_file: Option<u64>,
+
+    /// 只收录某个文件中的指定行，如 src/big.rs:1-200,900-1000（可重复）
+    #[arg(long = "lines", value_name = "PATH:RANGES", value_parser = parse_lines_spec)]
+    pub lines: Vec<(String, String)>,
+
+    /// 内容完全相同的文件只输出第一个，其余改为“Identical to”引用
+    #[arg(long = "dedup")]
+    pub dedup: bool,
+
+    /// 最大遍历深度，1 表示只收录项目目录下的直接文件
+    #[arg(long = "max-depth", value_name = "N")]
+    pub max_depth: Option<usize>,
+
+    /// 输入为 git 仓库地址时克隆的分支或标签
+    #[arg(long = "branch", value_name = "NAME")]
+    pub branch: Option<String>,
+
+    /// 不在文档开头输出目录树
+    #[arg(long = "no-tree")]
+    pub no_tree: bool,
+
+    /// 不输出文件目录（带锚点链接）
+    #[arg(long = "no-toc")]
+    pub no_toc: bool,
+
+    /// 在目录树之后加入文件间引用关系的 Mermaid 图：解析常见语言的 use/import/include，只画出项目内的文件（仅 Markdown）
+    #[arg(long = "import-graph")]
+    pub import_graph: bool,
+
+    /// 在目录之后加入符号索引：各文件顶层的函数、类型等定义，链接到所在文件（仅 Markdown）
+    #[arg(long = "symbols")]
+    pub symbols: bool,
+
+    /// 为每个目录输出二级标题，文件标题嵌套在其下（仅 Markdown）
+    #[arg(long = "group-by-dir")]
+    pub group_by_dir: bool,
+
+    /// 把每个文件的代码块包在 <details><summary>路径</summary> 中，GitHub 等查看器默认折叠（仅 Markdown）
+    #[arg(long = "collapsible")]
+    pub collapsible: bool,
+
+    /// 按目标查看器调整锚点、内部链接与折叠代码块：gfm（默认）、commonmark、gitlab、obsidian（仅 Markdown）
+    #[arg(long = "dialect", value_enum)]
+    pub dialect: Option<Dialect>,
+
+    /// 标题、摘要表头等固定文字的语言：en、zh（默认按系统语言选择）
+    #[arg(long = "locale", value_enum)]
+    pub locale: Option<Locale>,
+
+    /// 文件标题的级别（1–6，默认 2），目录树、目录等标题与之同级，嵌入其他文档时使用
+    #[arg(long = "heading-level", value_name = "N", value_parser = clap::value_parser!(u8).range(1..=6))]
+    pub heading_level: Option<u8>,
+
+    /// 文件标题的模板，{path} 处替换为路径（默认 "File: {path}"，中文为 "文件：{path}"；去掉该前缀后 extract 无法识别）
+    #[arg(long = "heading", value_name = "TEMPLATE")]
+    pub heading: Option<String>,
+
+    /// 在文档开头加入 YAML front matter：项目名、生成时间、版本、文件数与所用选项（仅 Markdown）
+    #[arg(long = "front-matter")]
+    pub front_matter: bool,
+
+    /// 放在文档最前面的一段说明（如给模型的提示词）；值为已存在的文件时读取其内容，否则按原文使用（JSON 格式不支持）
+    #[arg(long = "preamble", value_name = "FILE|TEXT")]
+    pub preamble: Option<String>,
+
+    /// 在代码块内每行前加上行号（JSON 格式不受影响，此时 extract 也无法原样还原）
+    #[arg(long = "line-numbers")]
+    pub line_numbers: bool,
+
+    /// 在文档开头输出项目摘要：文件数、行数、大小、按语言统计及跳过的文件数
+    #[arg(long = "summary")]
+    pub summary: bool,
+
+    /// 只输出项目摘要与 token 估算，不含任何文件内容，用于先清点目录
+    #[arg(long = "stats-only", conflicts_with_all = ["max_tokens", "max_output_size", "split_files", "manifest"])]
+    pub stats_only: bool,
+
+    /// 在文档末尾列出每个被跳过的文件及原因（二进制、过大、被忽略、无法读取等）
+    #[arg(long = "list-skipped")]
+    pub list_skipped: bool,
+
+    /// 二进制与过大的文件仍输出标题，并注明类型、大小与“skipped”，而不是直接省略
+    #[arg(long = "list-skipped-inline")]
+    pub list_skipped_inline: bool,
+
+    /// 在每个文件标题下注明大小、行数、修改时间与语言
+    #[arg(long = "metadata")]
+    pub metadata: bool,
+
+    /// 在每个文件标题下注明内容的 SHA-256，并在文档末尾附上清单，提取时据此校验
+    #[arg(long = "hashes")]
+    pub hashes: bool,
+
+    /// 统计每个文件及总计的 token 数并输出到 stderr
+    #[arg(long = "tokens")]
+    pub tokens: bool,
+
+    /// 在文档中加入 token 统计表（隐含 --tokens）
+    #[arg(long = "token-summary")]
+    pub token_summary: bool,
+
+    /// 文件内容的 token 总数上限：超出时先截断看起来是生成的大文件，再截断其余大文件，
+    /// 清单与入口文件保持完整；截断处与省略的文件都在文档中注明
+    #[arg(long = "budget", value_name = "TOKENS")]
+    pub budget: Option<usize>,
+
+    /// 按 token 上限把文档拆分为 name.part1.md、name.part2.md……
+    #[arg(long = "max-tokens", value_name = "N", conflicts_with = "stdout")]
+    pub max_tokens: Option<usize>,
+
+    /// 按字节上限拆分文档，支持 500k、5M 等写法
+    #[arg(long = "max-output-size", value_name = "SIZE", value_parser = parse_size,
+          conflicts_with_all = ["stdout", "max_tokens"])]
+    pub max_output_size: Option<u64>,
+
+    /// 每个源文件写成一页 .md，放在与源码结构相同的输出目录中（-o 指定，默认为 name-md/），另加 index.md
+    #[arg(long = "split-files", conflicts_with_all = ["stdout", "max_tokens", "max_output_size"])]
+    pub split_files: bool,
+
+    /// 在文档旁另写 <文档>.manifest.json，记录每个文件在文档中的位置、大小与 SHA-256，供 verify 校验
+    #[arg(long = "manifest", conflicts_with_all = ["stdout", "max_tokens", "max_output_size", "split_files"])]
+    pub manifest: bool,
+
+    /// 把生成的文档复制到剪贴板；除非同时给出 -o 或 -i，否则不写文件
+    #[arg(long = "clipboard", conflicts_with_all = ["max_tokens", "max_output_size", "split_files"])]
+    pub clipboard: bool,
+
+    /// 生成前在终端界面中手动勾选要收录的文件
+    #[arg(short = 'I', long = "interactive", conflicts_with = "watch")]
+    pub interactive: bool,
+
+    /// 只列出将被收录的文件（含大小）与被跳过的文件及原因，不写任何输出
+    #[arg(short = 'n', long = "dry-run", conflicts_with_all = ["watch", "interactive", "clipboard"])]
+    pub dry_run: bool,
+
+    /// 监视源目录，文件变更后自动重新生成
+    #[arg(short = 'w', long = "watch")]
+    pub watch: bool,
+
+    /// 不显示进度条
+    #[arg(long = "no-progress")]
+    pub no_progress: bool,
+
+    /// 不读取也不写入 .code2md-cache，重新读取全部文件
+    #[arg(long = "no-cache")]
+    pub no_cache: bool,
+
+    /// 仅输出匹配的文件（相对于项目目录的 glob，可重复）
+    #[arg(long = "include", value_name = "GLOB")]
+    pub include: Vec<String>,
+}
+
+impl GenerateArgs {
+    /// 读取 --files-from 给出的列表；标准输入只能读一次，因此在生成之前调用一次
+    pub fn read_files_from(&mut self) -> io::Result<()> {
+        let Some(source) = &self.files_from else { return Ok(()) };
+        let text = if source.as_os_str() == "-" {
+            let mut text = String::new();
+            io::stdin().read_to_string(&mut text)?;
+            text
+        } else {
+            fs::read_to_string(source)?
+        };
+        let separator = if text.contains('\0') { '\0' } else { '\n' };
+        self.files = Some(
+            text.split(separator)
+                .map(|line| line.trim_end_matches('\r'))
+                .filter(|line| !line.is_empty())
+                .map(PathBuf::from)
+                .collect(),
+        );
+        Ok(())
+    }
+
+    pub fn options(&self) -> ConversionOptions {
+        ConversionOptions {
+            save_inside: self.save_inside,
+            stdout: self.stdout,
+            no_file: self.no_file,
+            output: self.output.clone(),
+            name: self.name.clone(),
+            title: self.title.clone(),
+            relative_to: self.relative_to.clone(),
+            path_prefix: self.path_prefix.clone(),
+            exclude: self.exclude.clone(),
+            include: self.include.clone(),
+            format: self.format,
+            sort: self.sort,
+            git_only: self.git_only,
+            since: self.since.clone(),
+            branch: self.branch.clone(),
+            files: self.files.clone(),
+            follow_symlinks: self.follow_symlinks,
+            include_md: self.include_md,
+            hidden: self.hidden,
+            extract_pdf: self.extract_pdf,
+            max_depth: self.max_depth,
+            max_file_size: self.max_file_size,
+            max_tokens: self.max_tokens,
+            max_output_size: self.max_output_size,
+            budget: self.budget,
+            split_files: self.split_files,
+            manifest: self.manifest,
+            tree: !self.no_tree,
+            import_graph: self.import_graph,
+            toc: !self.no_toc,
+            symbols: self.symbols,
+            group_by_dir: self.group_by_dir,
+            collapsible: self.collapsible,
+            dialect: self.dialect.unwrap_or_default(),
+            locale: self.locale,
+            front_matter: self.front_matter,
+            preamble: self.preamble.clone(),
+            heading_level: self.heading_level.map(usize::from),
+            heading: self.heading.clone(),
+            line_numbers: self.line_numbers,
+            metadata: self.metadata,
+            hashes: self.hashes,
+            summary: self.summary,
+            stats_only: self.stats_only,
+            skipped_files: self.list_skipped,
+            skipped_inline: self.list_skipped_inline,
+            count_tokens: self.tokens,
+            token_summary: self.token_summary,
+            redact: !self.no_redact,
+            skip_secrets: self.skip_secrets,
+            strip_comments: self.strip_comments,
+            compact: self.compact,
+            outline: self.outline,
+            normalize_eol: self.normalize_eol,
+            max_lines_per_file: self.max_lines_per_file.map(|n| n as usize),
+            lines: self.lines.clone(),
+            dedup: self.dedup,
+            capture: self.clipboard,
+            selection: None,
+            progress: !self.no_progress && crate::logging::progress_allowed(),
+            cache: !self.no_cache,
+        }
+    }
+}
+
+/// `path:ranges`，按最后一个冒号切分，Windows 路径中的盘符不受影响
+fn parse_lines_spec(text: &str) -> Result<(String, String), String> {
+    let (path, ranges) = text.rsplit_once(':').ok_or_else(|| format!("应为 PATH:RANGES：{}", text))?;
+    parse_line_ranges(ranges)?;
+    Ok((path.to_string(), ranges.to_string()))
+}
+
+impl Cli {
+    /// 未指定子命令时按 generate 处理
+    pub fn into_command(self) -> Command {
+        self.command.unwrap_or(Command::Generate(self.generate))
+    }
+}
@@ -0,0 +1,276 @@
+use std::ops::RangeInclusive;
+
+/// 一种语言的注释与字符串写法
+struct Syntax {
+    line: &'static [&'static str],
+    block: &'static [(&'static str, &'static str)],
+    quotes: &'static [char],
+    /// `#` 之类的行注释只在行首或空白之后生效，避免误伤 `$#`、`${#x}`
+    line_needs_space: bool,
+    /// Rust 的 `'a` 生命周期与字符字面量需要区分
+    rust: bool,
+    /// Python 的三引号字符串
+    triple_quotes: bool,
+}
+
+const C_LIKE: Syntax = Syntax {
+    line: &["//"],
+    block: &[("/*", "*/")],
+    quotes: &['"', '\''],
+    line_needs_space: false,
+    rust: false,
+    triple_quotes: false,
+};
+
+const HASH: Syntax = Syntax {
+    line: &["#"],
+    block: &[],
+    quotes: &['"', '\''],
+    line_needs_space: true,
+    rust: false,
+    triple_quotes: false,
+};
+
+fn syntax_for(language: &str) -> Option<Syntax> {
+    Some(match language {
+        "rust" => Syntax { quotes: &['"'], rust: true, ..C_LIKE },
+        "c" | "cpp" | "csharp" | "java" | "kotlin" | "scala" | "groovy" | "swift" | "dart"
+        | "objectivec" | "zig" | "protobuf" | "fsharp" => C_LIKE,
+        "go" | "javascript" | "typescript" | "jsx" | "tsx" | "vue" | "svelte" => {
+            Syntax { quotes: &['"', '\'', '`'], ..C_LIKE }
+        }
+        "php" => Syntax { line: &["//", "#"], ..C_LIKE },
+        "css" => Syntax { line: &[], ..C_LIKE },
+        "scss" | "less" | "jsonc" => C_LIKE,
+        "hcl" => Syntax { line: &["//", "#"], ..C_LIKE },
+        "python" | "starlark" => Syntax { triple_quotes: true, ..HASH },
+        "ruby" | "bash" | "zsh" | "fish" | "perl" | "r" | "yaml" | "toml" | "makefile" | "dockerfile"
+        | "cmake" | "elixir" | "julia" | "nim" | "awk" | "tcl" | "gitignore" => HASH,
+        "powershell" => Syntax { block: &[("<#", "#>")], ..HASH },
+        "ini" => Syntax { line: &["#", ";"], ..HASH },
+        "sql" => Syntax { line: &["--"], quotes: &['\'', '"'], line_needs_space: false, ..C_LIKE },
+        "lua" => Syntax { line: &["--"], block: &[("--[[", "]]")], ..C_LIKE },
+        "haskell" => Syntax { line: &["--"], block: &[("{-", "-}")], quotes: &['"'], ..C_LIKE },
+        "html" | "xml" | "markdown" => Syntax { line: &[], block: &[("<!--", "-->")], quotes: &[], ..C_LIKE },
+        _ => return None,
+    })
+}
+
+/// 去掉注释，保留字符串中的内容与 shebang；只剩注释的行整行删除。
+/// 不认识的语言原样返回
+pub fn strip_comments(content: &str, language: &str) -> String {
+    let Some(syntax) = syntax_for(language) else { return content.to_string() };
+
+    let (shebang, body) = match content.strip_prefix("#!") {
+        Some(_) => content.split_at(content.find('\n').map_or(content.len(), |i| i + 1)),
+        None => ("", content),
+    };
+
+    let mut out = String::with_capacity(body.len());
+    // 当前行是否去掉过注释；这样的行若只剩空白就整行删除
+    let mut had_comment = false;
+    let mut lines: Vec<String> = Vec::new();
+    let mut rest = body;
+
+    while let Some(c) = rest.chars().next() {
+        if c == '\n' {
+            push_line(&mut lines, &mut out, had_comment);
+            had_comment = false;
+            rest = &rest[1..];
+            continue;
+        }
+
+        if let Some(marker) = syntax.block.iter().find(|(open, _)| rest.starts_with(open)) {
+            let end = rest[marker.0.len()..].find(marker.1).map_or(rest.len(), |i| i + marker.0.len() + marker.1.len());
+            // 块注释跨行时保留其中的换行，使后续行的结构不变
+            for _ in rest[..end].matches('\n') {
+                push_line(&mut lines, &mut out, true);
+            }
+            had_comment = true;
+            rest = &rest[end..];
+            continue;
+        }
+
+        if syntax.line.iter().any(|m| rest.starts_with(m))
+            && (!syntax.line_needs_space || out.is_empty() || out.ends_with(char::is_whitespace))
+        {
+            had_comment = true;
+            rest = &rest[rest.find('\n').unwrap_or(rest.len())..];
+            continue;
+        }
+
+        let literal = if syntax.rust {
+            rust_literal(rest)
+        } else if syntax.quotes.contains(&c) {
+            Some(quoted_literal(rest, syntax.triple_quotes, false))
+        } else {
+            None
+        };
+        if let Some(len) = literal {
+            out.push_str(&rest[..len]);
+            rest = &rest[len..];
+            continue;
+        }
+
+        out.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    if !out.is_empty() || had_comment {
+        push_line(&mut lines, &mut out, had_comment);
+    }
+
+    let mut result = String::from(shebang);
+    result.push_str(&lines.join("\n"));
+    if body.ends_with('\n') && !lines.is_empty() {
+        result.push('\n');
+    }
+    result
+}
+
+/// 结束一行：去掉注释留下的行尾空白，整行只有注释时丢弃；字符串跨行时行内可能含换行
+fn push_line(lines: &mut Vec<String>, out: &mut String, had_comment: bool) {
+    let line = std::mem::take(out);
+    if had_comment {
+        let trimmed = line.trim_end();
+        if !trimmed.trim().is_empty() {
+            lines.push(trimmed.to_string());
+        }
+    } else {
+        lines.push(line);
+    }
+}
+
+/// 以引号开头的字符串字面量的字节长度，处理反斜杠转义与 Python 三引号。
+/// 除 multiline 的语言与反引号模板字符串外，字符串不跨行，避免未闭合的引号吞掉整个文件
+fn quoted_literal(text: &str, triple_quotes: bool, multiline: bool) -> usize {
+    let quote = text.chars().next().unwrap_or('"');
+    if triple_quotes {
+        let triple = quote.to_string().repeat(3);
+        if text.starts_with(&triple) {
+            return text[3..].find(&triple).map_or(text.len(), |i| i + 6);
+        }
+    }
+
+    let mut escaped = false;
+    for (i, c) in text.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '\n' if quote != '`' && !multiline => return i,
+            c if c == quote => return i + 1,
+            _ => {}
+        }
+    }
+    text.len()
+}
+
+/// Rust 中的字符串、原始字符串与字符字面量；`'a` 生命周期不算
+fn rust_literal(text: &str) -> Option<usize> {
+    let raw = text.strip_prefix("br").or_else(|| text.strip_prefix('r'));
+    if let Some(after) = raw {
+        let hashes = after.len() - after.trim_start_matches('#').len();
+        if after[hashes..].starts_with('"') {
+            let prefix = text.len() - after.len() + hashes + 1;
+            let close = format!("\"{}", "#".repeat(hashes));
+            return Some(text[prefix..].find(&close).map_or(text.len(), |i| prefix + i + close.len()));
+        }
+    }
+
+    match text.chars().next()? {
+        '"' => Some(quoted_literal(text, false, true)),
+        '\'' => {
+            let mut chars = text.char_indices().skip(1);
+            match chars.next()? {
+                (_, '\\') => Some(quoted_literal(text, false, false)),
+                (_, c) => match chars.next() {
+                    Some((i, '\'')) if c != '\'' => Some(i + 1),
+                    _ => None,
+                },
+            }
+        }
+        _ => None,
+    }
+}
+
+/// 去掉行尾空白，把连续的空行合并为一行，并去掉开头与结尾的空行
+pub fn compact_whitespace(content: &str) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut blank_run = false;
+    for line in content.lines() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            blank_run = !out.is_empty();
+            continue;
+        }
+        if blank_run {
+            out.push('\n');
+            blank_run = false;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+/// 超过 max_lines 行时只保留开头与结尾各一半，中间换成 `... 1,234 lines omitted ...`
+pub fn head_tail(content: &str, max_lines: usize) -> Option<String> {
+    let lines: Vec<&str> = content.lines().collect();
+    if lines.len() <= max_lines {
+        return None;
+    }
+    let head = max_lines.div_ceil(2);
+    let tail = max_lines / 2;
+    let omitted = lines.len() - head - tail;
+    let mut out = lines[..head].join("\n");
+    out.push('\n');
+    out.push_str(&omission_marker(omitted));
+    out.push_str(&lines[lines.len() - tail..].join("\n"));
+    if tail > 0 && content.ends_with('\n') {
+        out.push('\n');
+    }
+    Some(out)
+}
+
+/// `... 1,234 lines omitted ...`，带换行
+fn omission_marker(omitted: usize) -> String {
+    match omitted {
+        1 => "... 1 line omitted ...\n".to_string(),
+        n => format!("... {} lines omitted ...\n", group_thousands(n)),
+    }
+}
+
+/// 1234567 -> "1,234,567"
+fn group_thousands(n: usize) -> String {
+    let digits = n.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// 只保留 ranges 中的行（从 1 开始），被跳过的部分换成 `... 1,234 lines omitted ...`
+pub fn select_lines(content: &str, ranges: &[RangeInclusive<usize>]) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut out = String::new();
+    let mut omitted = 0;
+    for (i, line) in lines.iter().enumerate() {
+        if !ranges.iter().any(|r| r.contains(&(i + 1))) {
+            omitted += 1;
+            continue;
+        }
+        if omitted > 0 {
+            out.push_str(&omission_marker(omitted));
+            omitted = 0;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    if omitted > 0 {
+        out.push_str(&omission_marker(omitted));
+    }
+    out
+}
@@ -0,0 +1,24 @@
+//! 由 clap 的命令定义生成各 shell 的补全脚本（clap_complete），选项变化时无需另行维护
+
+use clap::{Command, ValueEnum};
+use clap_complete::Shell as Target;
+use std::io::Write;
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Powershell,
+}
+
+pub fn generate(shell: Shell, mut command: Command, out: &mut impl Write) {
+    let target = match shell {
+        Shell::Bash => Target::Bash,
+        Shell::Zsh => Target::Zsh,
+        Shell::Fish => Target::Fish,
+        Shell::Powershell => Target::PowerShell,
+    };
+    let bin = command.get_name().to_string();
+    clap_complete::generate(target, &mut command, bin, out);
+}
@@ -0,0 +1,203 @@
+use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+use crate::format::Format;
+use crate::sort::SortOrder;
+
+pub const CONFIG_FILE_NAME: &str = "code2md.toml";
+
+/// 用户级配置的文件名，位于平台配置目录下的 code2md 目录中
+pub const USER_CONFIG_FILE_NAME: &str = "config.toml";
+
+/// 项目级配置，对应扫描根目录下的 code2md.toml；用户级配置格式相同，作为其默认值
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// 额外忽略的目录名
+    pub ignore_dirs: Vec<String>,
+    /// 额外忽略的文件名（不区分大小写）
+    pub ignore_files: Vec<String>,
+    /// 额外忽略的扩展名，带不带点均可
+    pub ignore_extensions: Vec<String>,
+    /// 从内置忽略目录中去掉的目录名，如 "bin"
+    pub unignore_dirs: Vec<String>,
+    /// 从内置忽略文件名中去掉的文件名（不区分大小写），如 "Cargo.lock"
+    pub unignore_files: Vec<String>,
+    /// 从内置忽略扩展名中去掉的扩展名，带不带点均可
+    pub unignore_extensions: Vec<String>,
+    /// 设为 false 时完全不使用内置的忽略目录、文件名与扩展名，只按上面的列表忽略
+    pub default_ignores: Option<bool>,
+    /// 额外排除的 glob 模式，相对于扫描根目录匹配；"!" 开头的为例外，以最后一个匹配的模式为准
+    pub exclude: Vec<String>,
+    /// 仅输出匹配的文件；为空时不限制
+    pub include: Vec<String>,
+    /// 单个文件的大小上限，可写字节数或 "256k" 这样的字符串
+    #[serde(deserialize_with = "deserialize_size")]
+    pub max_file_size: Option<u64>,
+    /// 输出文件路径，相对路径基于扫描根目录
+    pub output: Option<PathBuf>,
+    /// 等同于命令行 -i
+    pub inside: Option<bool>,
+    /// 输出格式
+    pub format: Option<Format>,
+    /// 文件排列顺序
+    pub sort: Option<SortOrder>,
+    /// 文件标题的级别（1–6），默认 2
+    pub heading_level: Option<usize>,
+    /// 文件标题的模板，{path} 处替换为路径，默认为所选语言的 "File: {path}"
+    pub heading: Option<String>,
+    /// 等同于命令行 --git-only
+    pub git_only: Option<bool>,
+    /// 扩展名到代码块语言标识的映射，覆盖内置表，如 `kts = "kotlin"`
+    pub languages: HashMap<String, String>,
+    /// 只收录这些文件中指定的行，如 `"src/big.rs" = "1-200,900-1000"`；键为文档标题中的路径，含 --path-prefix 的前缀
+    pub lines: HashMap<String, String>,
+    /// 是否遮盖疑似密钥，默认开启；设为 false 等同于命令行 --no-redact
+    pub redact: Option<bool>,
+    /// 等同于命令行 --skip-secrets
+    pub skip_secrets: Option<bool>,
+    /// 等同于命令行 --follow-symlinks
+    pub follow_symlinks: Option<bool>,
+    /// 等同于命令行 --include-md
+    pub include_md: Option<bool>,
+    /// 等同于命令行 --hidden
+    pub hidden: Option<bool>,
+    /// 等同于命令行 --extract-pdf
+    pub extract_pdf: Option<bool>,
+}
+
+impl Config {
+    /// 读取 root 下的配置文件并叠加在用户级配置之上；文件不存在时返回默认配置
+    pub fn load(root: &Path) -> io::Result<Config> {
+        let user = match user_config_path() {
+            Some(path) => Config::read(&path)?,
+            None => Config::default(),
+        };
+        Ok(Config::read(&root.join(CONFIG_FILE_NAME))?.over(user))
+    }
+
+    fn read(path: &Path) -> io::Result<Config> {
+        if !path.is_file() {
+            return Ok(Config::default());
+        }
+        tracing::debug!("读取配置 {}", path.display());
+        let text = fs::read_to_string(path)?;
+        toml::from_str(&text).map_err(|e| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), e))
+        })
+    }
+
+    /// 以 base 为默认值：单个取值以自身为准，列表与映射两者合并
+    fn over(mut self, base: Config) -> Config {
+        fn extend(own: &mut Vec<String>, base: Vec<String>) {
+            let mut merged = base;
+            merged.append(own);
+            *own = merged;
+        }
+        extend(&mut self.ignore_dirs, base.ignore_dirs);
+        extend(&mut self.ignore_files, base.ignore_files);
+        extend(&mut self.ignore_extensions, base.ignore_extensions);
+        extend(&mut self.unignore_dirs, base.unignore_dirs);
+        extend(&mut self.unignore_files, base.unignore_files);
+        extend(&mut self.unignore_extensions, base.unignore_extensions);
+        extend(&mut self.exclude, base.exclude);
+        extend(&mut self.include, base.include);
+        for (ext, language) in base.languages {
+            self.languages.entry(ext).or_insert(language);
+        }
+        for (path, ranges) in base.lines {
+            self.lines.entry(path).or_insert(ranges);
+        }
+        Config {
+            max_file_size: self.max_file_size.or(base.max_file_size),
+            output: self.output.or(base.output),
+            inside: self.inside.or(base.inside),
+            format: self.format.or(base.format),
+            sort: self.sort.or(base.sort),
+            heading_level: self.heading_level.or(base.heading_level),
+            heading: self.heading.or(base.heading),
+            git_only: self.git_only.or(base.git_only),
+            redact: self.redact.or(base.redact),
+            skip_secrets: self.skip_secrets.or(base.skip_secrets),
+            follow_symlinks: self.follow_symlinks.or(base.follow_symlinks),
+            include_md: self.include_md.or(base.include_md),
+            default_ignores: self.default_ignores.or(base.default_ignores),
+            hidden: self.hidden.or(base.hidden),
+            extract_pdf: self.extract_pdf.or(base.extract_pdf),
+            ..self
+        }
+    }
+}
+
+/// 用户级配置文件的位置：Windows 为 %APPDATA%\code2md\config.toml，
+/// 其他平台为 $XDG_CONFIG_HOME/code2md/config.toml（默认 ~/.config）
+pub fn user_config_path() -> Option<PathBuf> {
+    let dir = if cfg!(windows) {
+        PathBuf::from(std::env::var_os("APPDATA")?)
+    } else {
+        match std::env::var_os("XDG_CONFIG_HOME").filter(|v| !v.is_empty()) {
+            Some(dir) => PathBuf::from(dir),
+            None => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+        }
+    };
+    Some(dir.join("code2md").join(USER_CONFIG_FILE_NAME))
+}
+
+fn deserialize_size<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Size {
+        Bytes(u64),
+        Text(String),
+    }
+
+    match Size::deserialize(deserializer)? {
+        Size::Bytes(n) => Ok(Some(n)),
+        Size::Text(text) => parse_size(&text).map(Some).map_err(serde::de::Error::custom),
+    }
+}
+
+/// 解析 "1-200,900-1000"、"42"、"900-" 这样的行号范围（从 1 开始，含两端）
+pub fn parse_line_ranges(text: &str) -> Result<Vec<RangeInclusive<usize>>, String> {
+    let invalid = || format!("无效的行号范围：{}", text);
+    text.split(',')
+        .map(|part| {
+            let part = part.trim();
+            let (start, end) = match part.split_once('-') {
+                Some((start, "")) => (start.trim().parse().map_err(|_| invalid())?, usize::MAX),
+                Some((start, end)) => (
+                    start.trim().parse().map_err(|_| invalid())?,
+                    end.trim().parse().map_err(|_| invalid())?,
+                ),
+                None => {
+                    let line = part.parse().map_err(|_| invalid())?;
+                    (line, line)
+                }
+            };
+            if start == 0 || start > end {
+                return Err(invalid());
+            }
+            Ok(start..=end)
+        })
+        .collect()
+}
+
+/// 解析 1024、256k、5M、1G 这类大小写法（按 1024 进位）
+pub fn parse_size(text: &str) -> Result<u64, String> {
+    let text = text.trim();
+    let split = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+    let (digits, unit) = text.split_at(split);
+    let value: u64 = digits.parse().map_err(|_| format!("无效的大小：{}", text))?;
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" | "kib" => 1 << 10,
+        "m" | "mb" | "mib" => 1 << 20,
+        "g" | "gb" | "gib" => 1 << 30,
+        _ => return Err(format!("无法识别的单位：{}", unit)),
+    };
+    value.checked_mul(multiplier).ok_or_else(|| format!("大小超出范围：{}", text))
+}
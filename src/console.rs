@@ -0,0 +1,42 @@
+//! 程序以 windows 子系统构建，双击运行时不会弹出黑框；
+//! 但从终端启动时需要附着到父进程的控制台，帮助信息和 --stdout 输出才可见。
+
+/// 附着到父进程的控制台，返回是否成功
+#[cfg(windows)]
+pub fn attach() -> bool {
+    use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+    // 没有父控制台（如从资源管理器启动）时调用失败
+    unsafe { AttachConsole(ATTACH_PARENT_PROCESS) != 0 }
+}
+
+/// 为没有控制台的进程新开一个控制台窗口（--console）
+#[cfg(windows)]
+pub fn alloc() -> bool {
+    use windows_sys::Win32::System::Console::AllocConsole;
+    unsafe { AllocConsole() != 0 }
+}
+
+#[cfg(not(windows))]
+pub fn attach() -> bool {
+    true
+}
+
+#[cfg(not(windows))]
+pub fn alloc() -> bool {
+    false
+}
+
+/// 没有控制台（如从右键菜单或拖放启动）时错误无处显示，写到临时目录下的日志文件，
+/// 只保留最近一次的错误
+pub fn log_error(message: &str) {
+    let path = std::env::temp_dir().join("code2md-error.log");
+    let _ = std::fs::write(path, format!("{}\n", message.trim_end()));
+}
+
+/// 新开的控制台会随进程退出而关闭，等待用户按回车以便看清结果
+pub fn pause() {
+    use std::io::{self, BufRead, Write};
+    eprint!("按回车键退出…");
+    let _ = io::stderr().flush();
+    let _ = io::stdin().lock().read_line(&mut String::new());
+}
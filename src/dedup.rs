@@ -0,0 +1,20 @@
+use std::collections::HashMap;
+
+use crate::render::FileEntry;
+
+/// 按文档顺序找出内容完全相同的文件，后出现的记下首次出现者的显示路径，只输出引用
+pub fn mark_duplicates(entries: &mut [FileEntry]) {
+    let mut first: HashMap<&str, String> = HashMap::new();
+    let mut duplicates: Vec<(usize, String)> = Vec::new();
+    for (i, entry) in entries.iter().enumerate().filter(|(_, e)| e.is_included()) {
+        match first.get(entry.content.as_str()) {
+            Some(original) => duplicates.push((i, original.clone())),
+            None => {
+                first.insert(&entry.content, entry.display_path());
+            }
+        }
+    }
+    for (i, original) in duplicates {
+        entries[i].duplicate_of = Some(original);
+    }
+}
@@ -0,0 +1,329 @@
+use regex::Regex;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::OnceLock;
+
+use crate::render::FileEntry;
+
+/// 文件间的引用：(引用方, 被引用方)，均为 entries 中的下标，按下标排序、去重。
+/// 只认相对路径或项目内模块的 use/import/include，解析不到文档中某个文件的（第三方库、标准库）不计
+pub fn edges(entries: &[FileEntry]) -> Vec<(usize, usize)> {
+    let paths: Vec<String> = entries.iter().map(FileEntry::display_path).collect();
+    let index: HashMap<&str, usize> = entries
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| e.is_included() && e.duplicate_of.is_none())
+        .map(|(i, _)| (paths[i].as_str(), i))
+        .collect();
+    // Go 按目录组织包；go.mod 给出模块路径，据此把导入路径换算成目录
+    let mut packages: HashMap<&str, Vec<usize>> = HashMap::new();
+    let mut modules: Vec<(&str, &str)> = Vec::new();
+    for (&path, &i) in &index {
+        if path.ends_with(".go") && !path.ends_with("_test.go") {
+            packages.entry(parent(path)).or_default().push(i);
+        }
+        if path == "go.mod" || path.ends_with("/go.mod") {
+            let module = entries[i].content.lines().find_map(|line| line.trim().strip_prefix("module "));
+            modules.extend(module.map(|m| (parent(path), m.trim().trim_matches('"'))));
+        }
+    }
+    let resolver = Resolver { index: &index, packages, modules };
+
+    let mut edges = BTreeSet::new();
+    for (&path, &from) in &index {
+        let entry = &entries[from];
+        let targets = match entry.extension.as_str() {
+            "rs" => resolver.rust(path, &entry.content),
+            "py" | "pyi" => resolver.python(path, &entry.content),
+            "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts" | "vue" | "svelte" => resolver.javascript(path, &entry.content),
+            "c" | "h" | "cc" | "cpp" | "cxx" | "hh" | "hpp" | "hxx" | "m" | "mm" => resolver.include(path, &entry.content),
+            "java" | "kt" | "kts" | "scala" | "groovy" => resolver.java(&entry.content),
+            "go" => resolver.go(&entry.content),
+            _ => Vec::new(),
+        };
+        edges.extend(targets.into_iter().filter(|&to| to != from).map(|to| (from, to)));
+    }
+    edges.into_iter().collect()
+}
+
+struct Resolver<'a> {
+    /// 收录了内容的文件：显示路径 -> 下标
+    index: &'a HashMap<&'a str, usize>,
+    /// Go 包所在目录 -> 其中的非测试文件
+    packages: HashMap<&'a str, Vec<usize>>,
+    /// go.mod 所在目录与其中声明的模块路径
+    modules: Vec<(&'a str, &'a str)>,
+}
+
+impl Resolver<'_> {
+    fn get(&self, path: &str) -> Option<usize> {
+        self.index.get(path).copied()
+    }
+
+    /// 路径以 suffix 结尾（在 / 处对齐）的文件，有多个时取路径最短的
+    fn by_suffix(&self, suffix: &str) -> Option<usize> {
+        self.index
+            .iter()
+            .filter(|(path, _)| **path == suffix || path.ends_with(&format!("/{}", suffix)))
+            .min_by_key(|(path, _)| (path.len(), **path))
+            .map(|(_, &i)| i)
+    }
+
+    /// `use crate::a::b`、`use super::x`、`use self::y` 解析到 crate 中对应模块的文件
+    fn rust(&self, path: &str, content: &str) -> Vec<usize> {
+        static USE: OnceLock<Regex> = OnceLock::new();
+        static ALIAS: OnceLock<Regex> = OnceLock::new();
+        let re = USE.get_or_init(|| Regex::new(r"(?m)^\s*(?:pub(?:\([^)]*\))?\s+)?use\s+([^;]+);").unwrap());
+        let alias = ALIAS.get_or_init(|| Regex::new(r"\s+as\s+\w+").unwrap());
+        // crate 根为路径中最后一个 src 目录，没有时为文件所在目录
+        let dirs: Vec<&str> = path.split('/').collect();
+        let (dirs, file) = dirs.split_at(dirs.len() - 1);
+        let root_len = dirs.iter().rposition(|d| *d == "src").map_or(dirs.len(), |i| i + 1);
+        let root = dirs[..root_len].join("/");
+        // 当前模块相对于 crate 根的路径：a/b.rs 为 [a, b]，a/mod.rs 为 [a]，lib.rs 为 []
+        let mut module: Vec<&str> = dirs[root_len..].to_vec();
+        let stem = file[0].strip_suffix(".rs").unwrap_or(file[0]);
+        if !matches!(stem, "mod" | "lib" | "main") {
+            module.push(stem);
+        }
+
+        let mut targets = Vec::new();
+        for captures in re.captures_iter(content) {
+            let tree: String = alias.replace_all(&captures[1], "").split_whitespace().collect();
+            for used in expand_use(&tree) {
+                let mut segments = used.split("::").filter(|s| !s.is_empty());
+                let base: Vec<&str> = match segments.next() {
+                    Some("crate") => Vec::new(),
+                    Some("self") => module.clone(),
+                    Some("super") => {
+                        let mut base = module.clone();
+                        base.pop();
+                        while segments.clone().next() == Some("super") {
+                            segments.next();
+                            base.pop();
+                        }
+                        base
+                    }
+                    _ => continue,
+                };
+                let rest: Vec<&str> = segments.take_while(|s| *s != "*" && *s != "self").collect();
+                // 取能对应到文件的最长模块路径，其后是模块中的条目
+                let found = (0..=rest.len()).rev().find_map(|k| {
+                    let module: Vec<&str> = base.iter().chain(&rest[..k]).copied().collect();
+                    self.rust_module(&root, &module)
+                });
+                targets.extend(found);
+            }
+        }
+        targets
+    }
+
+    fn rust_module(&self, root: &str, module: &[&str]) -> Option<usize> {
+        let join = |name: &str| if root.is_empty() { name.to_string() } else { format!("{}/{}", root, name) };
+        if module.is_empty() {
+            return self.get(&join("lib.rs")).or_else(|| self.get(&join("main.rs")));
+        }
+        let module = module.join("/");
+        self.get(&join(&format!("{}.rs", module))).or_else(|| self.get(&join(&format!("{}/mod.rs", module))))
+    }
+
+    /// `import a.b`、`from a.b import c`、`from . import x`
+    fn python(&self, path: &str, content: &str) -> Vec<usize> {
+        static FROM: OnceLock<Regex> = OnceLock::new();
+        static IMPORT: OnceLock<Regex> = OnceLock::new();
+        let from = FROM.get_or_init(|| Regex::new(r"(?m)^\s*from\s+(\.*)([\w.]*)\s+import\s+\(?([\w \t,*]+)").unwrap());
+        let import = IMPORT.get_or_init(|| Regex::new(r"(?m)^\s*import\s+([\w.]+(?:\s*,\s*[\w.]+)*)").unwrap());
+        let module = |dir: &str, dotted: &str| {
+            let rel = dotted.replace('.', "/");
+            let base = join(dir, &rel);
+            self.get(&format!("{}.py", base)).or_else(|| self.get(&format!("{}/__init__.py", base)))
+        };
+        let absolute = |dotted: &str| {
+            let rel = dotted.replace('.', "/");
+            self.by_suffix(&format!("{}.py", rel)).or_else(|| self.by_suffix(&format!("{}/__init__.py", rel)))
+        };
+
+        let mut targets = Vec::new();
+        for captures in from.captures_iter(content) {
+            let (dots, dotted) = (captures[1].len(), &captures[2]);
+            let names = captures[3].split(',').map(str::trim).filter(|n| !n.is_empty() && *n != "*");
+            if dots > 0 {
+                let mut dir = parent(path).to_string();
+                for _ in 1..dots {
+                    dir = parent(&dir).to_string();
+                }
+                let mut found = false;
+                // from .pkg import mod 中的 mod 也可能是子模块
+                for name in names {
+                    let name = name.split_whitespace().next().unwrap_or_default();
+                    let sub = if dotted.is_empty() { name.to_string() } else { format!("{}.{}", dotted, name) };
+                    if let Some(i) = module(&dir, &sub) {
+                        targets.push(i);
+                        found = true;
+                    }
+                }
+                if !found && !dotted.is_empty() {
+                    targets.extend(module(&dir, dotted));
+                }
+            } else if !dotted.is_empty() {
+                let mut found = false;
+                for name in names {
+                    let name = name.split_whitespace().next().unwrap_or_default();
+                    if let Some(i) = absolute(&format!("{}.{}", dotted, name)) {
+                        targets.push(i);
+                        found = true;
+                    }
+                }
+                if !found {
+                    targets.extend(absolute(dotted));
+                }
+            }
+        }
+        for captures in import.captures_iter(content) {
+            targets.extend(captures[1].split(',').filter_map(|m| absolute(m.trim())));
+        }
+        targets
+    }
+
+    /// `import ... from './x'`、`require('../y')`、`import('./z')`，只认相对路径
+    fn javascript(&self, path: &str, content: &str) -> Vec<usize> {
+        static SPEC: OnceLock<Regex> = OnceLock::new();
+        let re = SPEC.get_or_init(|| {
+            Regex::new(r#"(?:\bfrom\s*|\bimport\s*\(?\s*|\brequire\s*\(\s*)['"](\.{1,2}/[^'"]*)['"]"#).unwrap()
+        });
+        const EXTENSIONS: &[&str] = &["", ".ts", ".tsx", ".js", ".jsx", ".mjs", ".cjs", ".mts", ".cts", ".vue", ".svelte"];
+        let dir = parent(path);
+        re.captures_iter(content)
+            .filter_map(|captures| {
+                let base = normalize(&join(dir, &captures[1]))?;
+                EXTENSIONS
+                    .iter()
+                    .find_map(|ext| self.get(&format!("{}{}", base, ext)))
+                    .or_else(|| EXTENSIONS[1..].iter().find_map(|ext| self.get(&format!("{}/index{}", base, ext))))
+            })
+            .collect()
+    }
+
+    /// `#include "x.h"`：先找同目录，再按路径结尾匹配
+    fn include(&self, path: &str, content: &str) -> Vec<usize> {
+        static INCLUDE: OnceLock<Regex> = OnceLock::new();
+        let re = INCLUDE.get_or_init(|| Regex::new(r#"(?m)^\s*#\s*(?:include|import)\s*"([^"]+)""#).unwrap());
+        let dir = parent(path);
+        re.captures_iter(content)
+            .filter_map(|captures| {
+                let included = &captures[1];
+                normalize(&join(dir, included)).and_then(|p| self.get(&p)).or_else(|| self.by_suffix(included.trim_start_matches("./")))
+            })
+            .collect()
+    }
+
+    /// `import a.b.C;`（含 import static）按包路径对应到 a/b/C.java 等文件
+    fn java(&self, content: &str) -> Vec<usize> {
+        static IMPORT: OnceLock<Regex> = OnceLock::new();
+        let re = IMPORT.get_or_init(|| Regex::new(r"(?m)^\s*import\s+(?:static\s+)?([\w.]+)").unwrap());
+        const EXTENSIONS: &[&str] = &["java", "kt", "kts", "scala", "groovy"];
+        re.captures_iter(content)
+            .filter_map(|captures| {
+                let mut parts: Vec<&str> = captures[1].split('.').collect();
+                // import static 的最后一段是成员名
+                while parts.len() > 1 {
+                    let rel = parts.join("/");
+                    if let Some(i) = EXTENSIONS.iter().find_map(|ext| self.by_suffix(&format!("{}.{}", rel, ext))) {
+                        return Some(i);
+                    }
+                    parts.pop();
+                }
+                None
+            })
+            .collect()
+    }
+
+    /// Go 按包（目录）引用：引用导入路径对应目录下的全部非测试文件
+    fn go(&self, content: &str) -> Vec<usize> {
+        static BLOCK: OnceLock<Regex> = OnceLock::new();
+        static SINGLE: OnceLock<Regex> = OnceLock::new();
+        static QUOTED: OnceLock<Regex> = OnceLock::new();
+        let block = BLOCK.get_or_init(|| Regex::new(r"(?s)\bimport\s*\((.*?)\)").unwrap());
+        let single = SINGLE.get_or_init(|| Regex::new(r#"(?m)^\s*import\s+(?:[\w.]+\s+)?"([^"]+)""#).unwrap());
+        let quoted = QUOTED.get_or_init(|| Regex::new(r#""([^"]+)""#).unwrap());
+        let mut imports: Vec<&str> = single.captures_iter(content).map(|c| c.get(1).unwrap().as_str()).collect();
+        for captures in block.captures_iter(content) {
+            imports.extend(quoted.captures_iter(captures.get(1).unwrap().as_str()).map(|c| c.get(1).unwrap().as_str()));
+        }
+
+        let mut targets = Vec::new();
+        for import in imports {
+            // 属于文档中某个模块的导入路径可以精确换算；否则按末尾至少两级目录对齐
+            let by_module = self.modules.iter().find_map(|(dir, module)| {
+                let rel = import.strip_prefix(module)?;
+                (rel.is_empty() || rel.starts_with('/')).then(|| join(dir, rel.trim_start_matches('/')))
+            });
+            let package = match by_module {
+                Some(dir) => self.packages.get(dir.as_str()),
+                None => self
+                    .packages
+                    .iter()
+                    .filter(|(dir, _)| dir.matches('/').count() >= 1 && (import == **dir || import.ends_with(&format!("/{}", dir))))
+                    .max_by_key(|(dir, _)| dir.len())
+                    .map(|(_, files)| files)
+                    .or_else(|| {
+                        let tail: Vec<&str> = import.rsplitn(3, '/').take(2).collect();
+                        let tail = format!("{}/{}", tail.get(1)?, tail[0]);
+                        self.packages.iter().filter(|(dir, _)| dir.ends_with(&tail)).min_by_key(|(dir, _)| dir.len()).map(|(_, f)| f)
+                    }),
+            };
+            targets.extend(package.into_iter().flatten().copied());
+        }
+        targets
+    }
+}
+
+/// `a::{b, c::{d, e}}` 展开为 `a::b`、`a::c::d`、`a::c::e`（输入已去掉别名与空白）
+fn expand_use(text: &str) -> Vec<String> {
+    let Some(open) = text.find('{') else {
+        return vec![text.to_string()];
+    };
+    let prefix = &text[..open];
+    let inner = text[open + 1..].strip_suffix('}').unwrap_or(&text[open + 1..]);
+    let mut items = Vec::new();
+    let (mut depth, mut start) = (0, 0);
+    for (i, c) in inner.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ',' if depth == 0 => {
+                items.push(&inner[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(&inner[start..]);
+    items
+        .into_iter()
+        .filter(|item| !item.is_empty())
+        .flat_map(|item| expand_use(item).into_iter().map(move |path| format!("{}{}", prefix, path)))
+        .collect()
+}
+
+fn parent(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(dir, _)| dir)
+}
+
+fn join(dir: &str, rel: &str) -> String {
+    if dir.is_empty() { rel.to_string() } else { format!("{}/{}", dir, rel) }
+}
+
+/// 去掉路径中的 . 与 ..；越过顶层时为 None
+fn normalize(path: &str) -> Option<String> {
+    let mut parts: Vec<&str> = Vec::new();
+    for part in path.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            _ => parts.push(part),
+        }
+    }
+    Some(parts.join("/"))
+}
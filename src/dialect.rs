@@ -0,0 +1,64 @@
+use clap::ValueEnum;
+use serde::Deserialize;
+
+/// Markdown 输出所针对的查看器，决定锚点、内部链接与折叠代码块的写法
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Dialect {
+    /// GitHub：按标题自动生成锚点，<details> 折叠
+    #[default]
+    Gfm,
+    /// 标准 CommonMark：标题没有锚点，在每个被链接的标题前插入 <a id>
+    Commonmark,
+    /// GitLab：锚点中连续的 - 合并为一个
+    Gitlab,
+    /// Obsidian：链接直接指向标题文本，折叠代码块写成可折叠的 callout
+    Obsidian,
+}
+
+impl Dialect {
+    /// 指向本文档中某个标题的链接；text 已转义，anchor 为 Slugger 按本方言生成的锚点
+    pub fn link(self, text: &str, heading: &str, anchor: &str) -> String {
+        match self {
+            Dialect::Obsidian => format!("[{}](#{})", text, obsidian_target(heading)),
+            _ => format!("[{}](#{})", text, anchor),
+        }
+    }
+
+    /// 被链接的标题之前需要单独写出的锚点（仅 CommonMark）
+    pub fn anchor_tag(self, anchor: &str) -> Option<String> {
+        (self == Dialect::Commonmark).then(|| format!("<a id=\"{}\"></a>\n\n", anchor))
+    }
+
+    /// 折叠代码块写成 callout，每行内容前加 `> `
+    pub fn quotes_collapsible(self) -> bool {
+        self == Dialect::Obsidian
+    }
+}
+
+/// Obsidian 的标题链接：去掉链接中不允许的 # | ^ : % [ ]，空白合并后按 URL 编码
+fn obsidian_target(heading: &str) -> String {
+    let cleaned: String = heading
+        .chars()
+        .map(|c| if "#|^:%[]".contains(c) { ' ' } else { c })
+        .collect();
+    let mut target = String::new();
+    for c in cleaned.split_whitespace().collect::<Vec<_>>().join(" ").chars() {
+        match c {
+            ' ' => target.push_str("%20"),
+            '(' | ')' | '<' | '>' | '`' | '\\' => target.push_str(&format!("%{:02X}", c as u32)),
+            c => target.push(c),
+        }
+    }
+    target
+}
+
+/// 把代码块等内容逐行放进 callout，空行只写 `>`
+pub fn quote(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + text.len() / 16);
+    for line in text.split_inclusive('\n') {
+        out.push_str(if line.trim_end_matches(['\n', '\r']).is_empty() { ">" } else { "> " });
+        out.push_str(line);
+    }
+    out
+}
@@ -0,0 +1,216 @@
+use chardetng::EncodingDetector;
+use encoding_rs::{DecoderResult, Encoding, UTF_16BE, UTF_16LE, UTF_8};
+use std::io::{self, Read};
+
+/// 流式解码时每次读取的字节数，也是判断编码所用的样本大小
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// 把源文件内容转为 UTF-8：先看 BOM 与无 BOM 的 UTF-16，再看是否本就是合法 UTF-8，
+/// 否则交给 chardetng 猜测编码（GBK、Shift-JIS、Latin-1 等）
+pub fn decode(bytes: &[u8]) -> String {
+    let (text, _) = detect(bytes, true).decode_with_bom_removal(bytes);
+    text.into_owned()
+}
+
+/// 逐行流式解码，line 含行尾的换行符（最后一行可能没有），整个文件不会同时留在内存中。
+/// 编码由开头的一块按 decode 的规则判断，之后遇到的非法字节以 U+FFFD 代替
+pub fn for_each_line(mut reader: impl Read, mut f: impl FnMut(&str) -> io::Result<()>) -> io::Result<()> {
+    let mut buffer = vec![0; CHUNK_SIZE];
+    let mut n = read_full(&mut reader, &mut buffer)?;
+    let mut decoder = detect(&buffer[..n], n < CHUNK_SIZE).new_decoder_with_bom_removal();
+    let mut text = String::new();
+    loop {
+        let last = n == 0;
+        text.reserve(decoder.max_utf8_buffer_length(n).unwrap_or(n * 3 + 16));
+        let _ = decoder.decode_to_string(&buffer[..n], &mut text, last);
+        // 只交出完整的行，不完整的一行留到下一块
+        let complete = if last { text.len() } else { text.rfind('\n').map_or(0, |i| i + 1) };
+        for line in text[..complete].split_inclusive('\n') {
+            f(line)?;
+        }
+        text.drain(..complete);
+        if last {
+            return Ok(());
+        }
+        n = reader.read(&mut buffer)?;
+    }
+}
+
+/// 尽量读满 buffer，返回读到的字节数；文件较短时小于 buffer 长度
+pub fn read_full(reader: &mut impl Read, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+/// 按 BOM、无 BOM 的 UTF-16、合法 UTF-8、chardetng 的顺序判断编码；
+/// complete 为 false 时 sample 只是开头一段，末尾被截断的多字节字符不算非法
+fn detect(sample: &[u8], complete: bool) -> &'static Encoding {
+    if let Some((encoding, _)) = Encoding::for_bom(sample) {
+        return encoding;
+    }
+    if let Some(encoding) = sniff_utf16(sample) {
+        return encoding;
+    }
+    match std::str::from_utf8(sample) {
+        Ok(_) => return UTF_8,
+        Err(e) if !complete && e.error_len().is_none() => return UTF_8,
+        Err(_) => {}
+    }
+    let mut detector = EncodingDetector::new();
+    detector.feed(sample, complete);
+    detector.guess(None, false)
+}
+
+/// 把 CRLF 与单独的 CR 统一为 LF；第二项表示原文是否混用了 CRLF 与 LF
+pub fn normalize_eol(text: &str) -> (String, bool) {
+    let crlf = text.matches("\r\n").count();
+    let mixed = crlf > 0 && text.matches('\n').count() > crlf;
+    (text.replace("\r\n", "\n").replace('\r', "\n"), mixed)
+}
+
+/// 判断文本与二进制所用的开头样本大小
+pub const SAMPLE_SIZE: usize = 8 * 1024;
+
+/// 样本被判为二进制的原因
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Binary {
+    /// 文件头是已知的二进制格式，内容为 infer 给出的扩展名
+    Signature(&'static str),
+    NullByte,
+    /// 制表符、换行等以外的控制字符过多
+    ControlBytes,
+    /// 大部分不是合法 UTF-8，按猜测的编码也无法解码
+    Undecodable,
+}
+
+impl Binary {
+    pub fn describe(self) -> String {
+        match self {
+            Binary::Signature(name) => format!("文件头表明格式为 {}", name.to_uppercase()),
+            Binary::NullByte => "开头的内容中有空字节".to_string(),
+            Binary::ControlBytes => "开头的内容中控制字符过多".to_string(),
+            Binary::Undecodable => "开头的内容既不是 UTF-8，也不能按其他编码解码".to_string(),
+        }
+    }
+}
+
+/// 根据开头的样本判断是否为二进制，None 表示文本。依次看：
+/// BOM 与无 BOM 的 UTF-16（算文本）、infer 识别的文件头、空字节、控制字符的比例；
+/// 不足 95% 是合法 UTF-8 时，还要能按 chardetng 猜出的编码（GBK 等）无错解码。
+/// complete 为 false 时 sample 只是开头一段，末尾被截断的多字节字符不算错
+pub fn sniff_binary(sample: &[u8], complete: bool) -> Option<Binary> {
+    if Encoding::for_bom(sample).is_some() || sniff_utf16(sample).is_some() {
+        return None;
+    }
+    // 不少格式在开头几 KiB 内没有空字节（字体、压缩包、wasm 的某些段），只看内容会误判为文本；
+    // 但 ID3、RIFF、OTTO 这类全是可打印字符的魔数也可能只是文本的开头，这时还要内容本身不像文本
+    if let Some(kind) = infer::get(sample).filter(|kind| kind.matcher_type() != infer::MatcherType::Text) {
+        let signature = Binary::Signature(kind.extension());
+        let head = &sample[..sample.len().min(8)];
+        if !head.iter().all(|&b| b.is_ascii_graphic() || b.is_ascii_whitespace()) {
+            return Some(signature);
+        }
+        return sniff_content(sample, complete).map(|_| signature);
+    }
+    sniff_content(sample, complete)
+}
+
+/// 不看文件头，只按空字节、控制字符的比例与能否解码判断
+fn sniff_content(sample: &[u8], complete: bool) -> Option<Binary> {
+    if sample.contains(&0) {
+        return Some(Binary::NullByte);
+    }
+    // 退格、换页与 ESC（终端颜色）在文本中也会出现
+    let controls = sample
+        .iter()
+        .filter(|&&b| (b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r' | 0x08 | 0x0b | 0x0c | 0x1b)) || b == 0x7f)
+        .count();
+    if controls * 20 > sample.len() {
+        return Some(Binary::ControlBytes);
+    }
+    let valid: usize = sample.utf8_chunks().map(|chunk| chunk.valid().len()).sum();
+    if valid * 100 >= sample.len() * 95 {
+        return None;
+    }
+    let mut detector = EncodingDetector::new();
+    detector.feed(sample, complete);
+    let mut decoder = detector.guess(None, false).new_decoder_without_bom_handling();
+    let mut text = String::with_capacity(decoder.max_utf8_buffer_length_without_replacement(sample.len()).unwrap_or(sample.len() * 3));
+    match decoder.decode_to_string_without_replacement(sample, &mut text, complete) {
+        (DecoderResult::Malformed(..), _) => Some(Binary::Undecodable),
+        _ => None,
+    }
+}
+
+/// 识别没有 BOM 的 UTF-16：以 ASCII 为主的文本每个字符都有一个零字节，
+/// 且零字节只出现在奇数位（LE）或偶数位（BE）之一
+fn sniff_utf16(bytes: &[u8]) -> Option<&'static Encoding> {
+    let sample = &bytes[..bytes.len().min(1024) & !1];
+    let pairs = sample.len() / 2;
+    if pairs == 0 {
+        return None;
+    }
+
+    let (mut even, mut odd) = (0, 0);
+    for pair in sample.chunks_exact(2) {
+        match (pair[0], pair[1]) {
+            (0, 0) => return None,
+            (0, _) => even += 1,
+            (_, 0) => odd += 1,
+            _ => {}
+        }
+    }
+
+    // 至少一半字符是 ASCII，另一侧不能出现零字节
+    if odd * 2 >= pairs && even == 0 {
+        Some(UTF_16LE)
+    } else if even * 2 >= pairs && odd == 0 {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii_magic_at_start_of_text_is_text() {
+        assert_eq!(sniff_binary(b"ID3,name\n1,alice\n2,bob\n", true), None);
+        assert_eq!(sniff_binary(b"RIFF notes\nsecond line\n", true), None);
+        assert_eq!(sniff_binary(b"OTTO was here\n", true), None);
+        assert_eq!(sniff_binary(b"fLaC and OggS are audio formats\n", true), None);
+        assert_eq!(sniff_binary(b"BZh is the bzip2 magic\n", true), None);
+    }
+
+    #[test]
+    fn ascii_magic_with_binary_content_is_signature() {
+        let mut font = b"OTTO\x00\x0b\x00\x80\x00\x03\x00\x30CFF ".to_vec();
+        font.extend_from_slice(&[0; 64]);
+        assert_eq!(sniff_binary(&font, true), Some(Binary::Signature("otf")));
+        let mp3 = b"ID3\x04\x00\x00\x00\x00\x00\x23TSSE\x00\x00\x00\x0f\x00\x00";
+        assert_eq!(sniff_binary(mp3, true), Some(Binary::Signature("mp3")));
+    }
+
+    #[test]
+    fn binary_magic_wins_outright() {
+        let png = b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR";
+        assert_eq!(sniff_binary(png, true), Some(Binary::Signature("png")));
+        assert_eq!(sniff_binary(b"\x00asm\x01\x00\x00\x00", true), Some(Binary::Signature("wasm")));
+    }
+
+    #[test]
+    fn plain_text_and_utf16() {
+        assert_eq!(sniff_binary(b"fn main() {}\n", true), None);
+        assert_eq!(sniff_binary("中文注释\n".as_bytes(), true), None);
+        assert_eq!(sniff_binary(b"h\x00e\x00l\x00l\x00o\x00\n\x00", true), None);
+        assert_eq!(sniff_binary(b"abc\x00def", true), Some(Binary::NullByte));
+    }
+}
@@ -0,0 +1,42 @@
+use std::io;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// 转换失败的原因；命令行程序据此选择退出码
+#[derive(Debug, Error)]
+pub enum Error {
+    /// 选项组合无效，如分卷输出写到标准输出
+    #[error("{0}")]
+    InvalidOptions(String),
+    #[error("找不到 {}", .0.display())]
+    PathNotFound(PathBuf),
+    #[error("无法写入 {}：{source}", .path.display())]
+    OutputUnwritable { path: PathBuf, source: io::Error },
+    #[error("没有可收录的文件（--dry-run 可查看各文件被跳过的原因）")]
+    NoFiles,
+    /// check 发现已有的文档与源码不一致
+    #[error("{} 已过期，请重新生成", .0.display())]
+    OutOfDate(PathBuf),
+    /// 提取出的文件与文档中记录的 SHA-256 不符
+    #[error("{0} 个文件的内容与记录的 SHA-256 不符，可能已损坏或被截断")]
+    HashMismatch(usize),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+impl Error {
+    /// 命令行程序的退出码：1 为其他错误，2 与参数解析错误一致
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Io(_) => 1,
+            Error::InvalidOptions(_) => 2,
+            Error::PathNotFound(_) => 3,
+            Error::OutputUnwritable { .. } => 4,
+            Error::NoFiles => 5,
+            Error::OutOfDate(_) => 6,
+            Error::HashMismatch(_) => 7,
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,156 @@
+use ignore::gitignore::Gitignore;
+use ignore::Match;
+use indicatif::HumanBytes;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::encoding;
+use crate::filter::{self, IGNORE_FILE_NAME};
+use crate::job::Job;
+use crate::pathname;
+use crate::progress::Progress;
+use crate::redact;
+use crate::stats::Skip;
+use crate::{ConversionOptions, Error, Result};
+
+/// 判断过程中的一步
+pub struct Step {
+    /// false 表示文件在这一步被排除，之后的步骤不再进行
+    pub passed: bool,
+    pub text: String,
+}
+
+/// 按与生成文档时相同的顺序逐条检查 file 是否会被收录，返回到第一条不通过的规则为止的各步
+pub fn explain(paths: &[PathBuf], options: &ConversionOptions, file: &Path) -> Result<Vec<Step>> {
+    let job = Job::new(paths, &ConversionOptions { cache: false, ..options.clone() }, Progress::new(false))?;
+    let file = file.canonicalize().map_err(|_| Error::PathNotFound(file.to_path_buf()))?;
+    let Some(index) = job.roots.iter().position(|r| file.starts_with(&r.path)) else {
+        return Err(Error::InvalidOptions(format!("{} 不在任何项目目录中", file.display())));
+    };
+    let root = &job.roots[index];
+    let filter = &root.filter;
+
+    let mut steps = Vec::new();
+    let mut step = |passed: bool, text: String| {
+        steps.push(Step { passed, text });
+        passed
+    };
+    if !step(file.is_file(), format!("{} 位于 {} 下", job.display_path(index, &file), root.path.display())) {
+        return Ok(steps);
+    }
+
+    if let Some(listed) = job.listed_files(root)? {
+        let source = if job.files.is_some() {
+            "--files-from 给出的文件"
+        } else if job.since.is_some() {
+            "git diff 列出的改动"
+        } else {
+            "git ls-files 列出的文件"
+        };
+        if !step(listed.contains(&file), format!("在{}中", source)) {
+            return Ok(steps);
+        }
+        if job.files.is_none() && filter.rejects_listed(&file) {
+            let reason = filter.name_rejection(&file, false).unwrap_or_else(|| format!("被 {} 忽略", IGNORE_FILE_NAME));
+            step(false, reason);
+            return Ok(steps);
+        }
+        step(true, "文件名与排除模式".to_string());
+    } else {
+        // 遍历器自上而下剪掉目录，第一个被剪掉的目录决定结果
+        let rel_dirs: Vec<&Path> = file.ancestors().skip(1).take_while(|d| *d != root.path).collect();
+        for dir in rel_dirs.into_iter().rev() {
+            if let Some(reason) = filter.name_rejection(dir, true) {
+                step(false, reason);
+                return Ok(steps);
+            }
+        }
+        if let Some(reason) = filter.name_rejection(&file, false) {
+            step(false, reason);
+            return Ok(steps);
+        }
+        step(true, "目录名、文件名与排除模式".to_string());
+        if let Some(reason) = ignore_rule(&file) {
+            step(false, reason);
+            return Ok(steps);
+        }
+        step(true, format!(".gitignore 与 {}", IGNORE_FILE_NAME));
+    }
+
+    if !step(job.within_depth(root, &file), "在 --max-depth 限制内".to_string()) {
+        return Ok(steps);
+    }
+    if !step(!job.is_own_output(&file), "不是输出文件或缓存本身".to_string()) {
+        return Ok(steps);
+    }
+
+    if let Err(reason) = filter.check_file(&file) {
+        step(false, describe(&file, reason, filter.size_limit(&file)));
+        return Ok(steps);
+    }
+    step(true, "包含模式、扩展名、大小上限与文本检测".to_string());
+
+    let content = match fs::read(pathname::extended(&file)) {
+        Ok(bytes) => encoding::decode(&bytes),
+        Err(e) => {
+            step(false, format!("无法读取：{}", e));
+            return Ok(steps);
+        }
+    };
+    if !step(!content.trim().is_empty(), "内容不为空".to_string()) {
+        return Ok(steps);
+    }
+    if filter.skip_secrets() && !step(!redact::contains_private_key(&content), "不含私钥（--skip-secrets）".to_string()) {
+        return Ok(steps);
+    }
+    Ok(steps)
+}
+
+/// 各级目录中的 .gitignore、.code2mdignore 与全局 gitignore 中决定 file 去留的规则：
+/// 越深的目录优先，同一目录中 .code2mdignore 优先于 .gitignore
+fn ignore_rule(file: &Path) -> Option<String> {
+    let mut matchers: Vec<Gitignore> = Vec::new();
+    for dir in file.ancestors().skip(1) {
+        for name in [IGNORE_FILE_NAME, ".gitignore"] {
+            let path = dir.join(name);
+            if path.is_file() {
+                matchers.push(Gitignore::new(&path).0);
+            }
+        }
+    }
+    matchers.push(Gitignore::global().0);
+
+    for matcher in &matchers {
+        match matcher.matched_path_or_any_parents(file, false) {
+            Match::Ignore(glob) => {
+                let source = glob.from().map_or_else(|| "全局 gitignore".to_string(), |p| p.display().to_string());
+                return Some(format!("被 {} 中的 {} 忽略", source, glob.original()));
+            }
+            Match::Whitelist(_) => return None,
+            Match::None => {}
+        }
+    }
+    None
+}
+
+/// 内容层面的检查不通过时的具体说明
+fn describe(file: &Path, reason: Skip, max_file_size: u64) -> String {
+    match reason {
+        Skip::Extension => format!(
+            "扩展名 .{} 在忽略列表中",
+            file.extension().unwrap_or_default().to_string_lossy().to_lowercase()
+        ),
+        Skip::TooLarge => format!(
+            "大小 {} 超过上限 {}（--max-file-size 可调整）",
+            HumanBytes(fs::metadata(file).map_or(0, |m| m.len())),
+            HumanBytes(max_file_size)
+        ),
+        Skip::Binary => match filter::binary_kind(file) {
+            Ok(Some(kind)) => format!("{}，判断为二进制文件", kind.describe()),
+            _ => "判断为二进制文件".to_string(),
+        },
+        Skip::NotIncluded => "不匹配任何包含模式（--include）".to_string(),
+        Skip::Secret => "文件名看起来是密钥文件（--skip-secrets）".to_string(),
+        other => other.describe().to_string(),
+    }
+}
@@ -0,0 +1,298 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use crate::pathname::ESCAPED_MARKER;
+use crate::locale::{Locale, Strings};
+use crate::render::{DETAILS_OPEN, SHA256_PREFIX};
+use crate::sha256;
+
+/// 从生成的文档中解析出的一个文件
+pub struct ExtractedFile {
+    pub path: String,
+    pub content: String,
+    /// 文档中记录的 SHA-256（--hashes）
+    pub sha256: Option<String>,
+}
+
+impl ExtractedFile {
+    /// 内容与记录的 SHA-256 一致；没有记录时为 None
+    pub fn verified(&self) -> Option<bool> {
+        self.sha256.as_ref().map(|hash| sha256::hex(self.content.as_bytes()) == *hash)
+    }
+}
+
+/// 文档中的一个文件小节；超大文件在分卷时会被切成多段
+pub struct Piece {
+    pub path: String,
+    /// `path (i/n)` 中的 i，未切分时为 None
+    pub index: Option<usize>,
+    pub content: String,
+    /// --dedup 输出的引用：内容与该文件（完整显示路径）相同
+    pub duplicate_of: Option<String>,
+    /// 标题下 `> SHA-256: ` 行记录的摘要，只在第一段中出现
+    pub sha256: Option<String>,
+}
+
+/// 去掉任一语言下的固定前缀，如 "File: " 或 "文件："
+fn strip_localized(text: &str, prefix: impl Fn(&Strings) -> &'static str) -> Option<&str> {
+    Locale::ALL.iter().find_map(|locale| text.strip_prefix(prefix(locale.strings())))
+}
+
+/// 解析 `## File: path`（或其他级别、其他语言的）标题及其后的第一个代码块
+pub fn parse(markdown: &str) -> Vec<Piece> {
+    let mut pieces: Vec<Piece> = Vec::new();
+    let mut lines = markdown.split('\n').peekable();
+    // 多根目录文档中 `# Root: x` 之后的文件都位于 x/ 下
+    let mut group: Option<&str> = None;
+
+    while let Some(line) = lines.next() {
+        // 标题的级别随 --heading-level、--group-by-dir 变化
+        let Some(heading) = heading_text(line) else { continue };
+        if let Some(root) = strip_localized(heading, |s| s.root_prefix) {
+            group = Some(root.trim_end_matches('\r'));
+            continue;
+        }
+        let Some(label) = strip_localized(heading, |s| s.file_prefix) else { continue };
+        let label = label.trim_end_matches('\r');
+        // 转义过的路径照原样作为文件名，不还原为原始字节
+        let label = label.strip_suffix(ESCAPED_MARKER).unwrap_or(label);
+
+        let (path, index) = match split_piece(label) {
+            Some((path, index)) => (path, Some(index)),
+            None => (label, None),
+        };
+        let path = match group {
+            Some(root) => format!("{}/{}", root, path),
+            None => path.to_string(),
+        };
+
+        // 跳到代码块起始行；遇到重复文件的引用行则没有代码块
+        let mut sha256 = None;
+        // Obsidian 方言的折叠 callout 中，代码块的每一行都以 `>` 开头
+        let mut quoted = false;
+        let fence = loop {
+            match lines.peek() {
+                Some(l) if strip_localized(l, |s| s.duplicate_prefix).is_some() => {
+                    let original = strip_localized(l, |s| s.duplicate_prefix).unwrap_or_default().trim_end().trim_matches('`');
+                    pieces.push(Piece { path: path.clone(), index, content: String::new(), duplicate_of: Some(original.to_string()), sha256: None });
+                    lines.next();
+                    break None;
+                }
+                Some(l) if l.starts_with("> ") && l[2..].starts_with(SHA256_PREFIX) => {
+                    sha256 = Some(l[2 + SHA256_PREFIX.len()..].trim_end().to_string());
+                    lines.next();
+                }
+                Some(l) if l.starts_with("> ") && fence_of(&l[2..]).is_some() => {
+                    quoted = true;
+                    let f = fence_of(&l[2..]);
+                    lines.next();
+                    break f;
+                }
+                // 空行、--metadata 输出的引用行与 --collapsible 的折叠标签
+                Some(l) if l.trim().is_empty() || l.starts_with('>') => { lines.next(); }
+                Some(l) if l.starts_with(DETAILS_OPEN) || l.starts_with("<summary>") => { lines.next(); }
+                Some(l) => match fence_of(l) {
+                    Some(f) => { lines.next(); break Some(f); }
+                    None => break None,
+                },
+                None => break None,
+            }
+        };
+        let Some((fence_char, fence_len)) = fence else { continue };
+
+        let mut body: Vec<&str> = Vec::new();
+        for l in lines.by_ref() {
+            let l = if quoted { unquote(l) } else { l };
+            if is_closing_fence(l, fence_char, fence_len) { break; }
+            body.push(l);
+        }
+        let content = body.join("\n");
+        pieces.push(Piece { path, index, content, duplicate_of: None, sha256 });
+    }
+    pieces
+}
+
+/// 把同一文件的各段按序号拼接回去；分卷文件的给出顺序不影响结果
+pub fn assemble(pieces: Vec<Piece>) -> Vec<ExtractedFile> {
+    let mut order: Vec<String> = Vec::new();
+    let mut groups: HashMap<String, Vec<Piece>> = HashMap::new();
+    for piece in pieces {
+        if !groups.contains_key(&piece.path) {
+            order.push(piece.path.clone());
+        }
+        groups.entry(piece.path.clone()).or_default().push(piece);
+    }
+
+    let mut files: Vec<ExtractedFile> = Vec::with_capacity(order.len());
+    for path in order {
+        let mut group = groups.remove(&path).unwrap_or_default();
+        group.sort_by_key(|p| p.index.unwrap_or(0));
+        let group_sha256 = group.iter().find_map(|p| p.sha256.clone());
+        // 引用总在原文件之后出现，此时原文件已经拼好
+        let content = match group.iter().find_map(|p| p.duplicate_of.as_deref()) {
+            Some(original) => files.iter().find(|f| f.path == original).map(|f| f.content.clone()).unwrap_or_default(),
+            None => group.into_iter().map(|p| p.content).collect::<Vec<_>>().join("\n"),
+        };
+        let sha256 = group_sha256;
+        files.push(ExtractedFile { path, content, sha256 });
+    }
+    files
+}
+
+/// 任意级别的 ATX 标题的文本
+fn heading_text(line: &str) -> Option<&str> {
+    let rest = line.trim_start_matches('#');
+    let level = line.len() - rest.len();
+    if (1..=6).contains(&level) { rest.strip_prefix(' ') } else { None }
+}
+
+/// 起始围栏：至少三个反引号或波浪线，后接语言标识
+fn fence_of(line: &str) -> Option<(char, usize)> {
+    let c = line.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = line.chars().take_while(|x| *x == c).count();
+    (len >= 3).then_some((c, len))
+}
+
+/// 去掉 callout 中行首的 `> `（空行只有 `>`）
+fn unquote(line: &str) -> &str {
+    line.strip_prefix("> ").or_else(|| line.strip_prefix('>')).unwrap_or(line)
+}
+
+fn is_closing_fence(line: &str, c: char, len: usize) -> bool {
+    let line = line.trim_end();
+    line.chars().count() >= len && line.chars().all(|x| x == c)
+}
+
+/// 识别 "path (i/n)" 形式的分段标题
+fn split_piece(label: &str) -> Option<(&str, usize)> {
+    let rest = label.strip_suffix(')')?;
+    let open = rest.rfind(" (")?;
+    let (index, total) = rest[open + 2..].split_once('/')?;
+    let index: usize = index.parse().ok()?;
+    total.parse::<usize>().ok()?;
+    Some((&rest[..open], index))
+}
+
+/// 拒绝绝对路径与 `..`，防止文档把文件写到目标目录之外
+pub fn safe_join(root: &Path, rel: &str) -> Option<PathBuf> {
+    let rel = Path::new(rel);
+    let mut out = root.to_path_buf();
+    for comp in rel.components() {
+        match comp {
+            Component::Normal(part) => out.push(part),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    (out != root).then_some(out)
+}
+
+pub struct ExtractReport {
+    pub written: usize,
+    pub skipped_existing: Vec<String>,
+    pub rejected: Vec<String>,
+}
+
+pub fn write_files(files: &[ExtractedFile], root: &Path, force: bool) -> io::Result<ExtractReport> {
+    let mut report = ExtractReport { written: 0, skipped_existing: Vec::new(), rejected: Vec::new() };
+    for file in files {
+        let Some(target) = safe_join(root, &file.path) else {
+            report.rejected.push(file.path.clone());
+            continue;
+        };
+        if target.exists() && !force {
+            report.skipped_existing.push(file.path.clone());
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&target, &file.content)?;
+        report.written += 1;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{convert, ConversionOptions};
+
+    /// 在临时目录中写出 files，生成文档后再还原，按路径返回还原出的内容
+    fn roundtrip(files: &[(&str, &str)], options: ConversionOptions) -> Vec<(String, String)> {
+        let dir = tempfile::tempdir().unwrap();
+        for (path, content) in files {
+            let path = dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        let options = ConversionOptions { capture: true, tree: false, toc: false, ..options };
+        let document = convert(dir.path(), &options).unwrap().document.unwrap();
+        assemble(parse(&document)).into_iter().map(|f| (f.path, f.content)).collect()
+    }
+
+    fn owned(files: &[(&str, &str)]) -> Vec<(String, String)> {
+        files.iter().map(|(p, c)| (p.to_string(), c.to_string())).collect()
+    }
+
+    #[test]
+    fn long_backtick_runs_inside_fence() {
+        let files = [("a.md.txt", "before\n```rust\nfn main() {}\n```\n`````\nafter\n"), ("b.rs", "let s = \"~~~\";\n")];
+        assert_eq!(roundtrip(&files, ConversionOptions::default()), owned(&files));
+    }
+
+    #[test]
+    fn crlf_content_is_kept() {
+        let files = [("a.txt", "one\r\ntwo\r\n"), ("b.txt", "mixed\r\nlf\n")];
+        assert_eq!(roundtrip(&files, ConversionOptions::default()), owned(&files));
+    }
+
+    #[test]
+    fn crlf_document() {
+        let markdown = "## File: a.rs\r\n\r\n```rust\r\nfn a() {}\r\n```\r\n";
+        let files = assemble(parse(markdown));
+        assert_eq!(files[0].path, "a.rs");
+        assert_eq!(files[0].content, "fn a() {}\r");
+    }
+
+    #[test]
+    fn duplicate_references_copy_the_original() {
+        let files = [("a.txt", "same\n"), ("b.txt", "same\n")];
+        let options = ConversionOptions { dedup: true, ..Default::default() };
+        assert_eq!(roundtrip(&files, options), owned(&files));
+    }
+
+    #[test]
+    fn split_parts_in_any_order() {
+        let markdown = "## File: big.txt (2/2)\n\n```text\nsecond\n```\n\n## File: big.txt (1/2)\n\n```text\nfirst\n```\n";
+        let files = assemble(parse(markdown));
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].content, "first\nsecond");
+    }
+
+    #[test]
+    fn multi_root_groups() {
+        let markdown = "# Root: web\n\n## File: src/a.txt\n\n```text\n1\n```\n\n# Root: api\n\n## File: src/a.txt\n\n```text\n2\n```\n";
+        let files: Vec<(String, String)> = assemble(parse(markdown)).into_iter().map(|f| (f.path, f.content)).collect();
+        assert_eq!(files, owned(&[("web/src/a.txt", "1"), ("api/src/a.txt", "2")]));
+    }
+
+    #[test]
+    fn localized_headings() {
+        let files = [("a.txt", "内容\n"), ("b.txt", "内容\n")];
+        let options = ConversionOptions { locale: Some(Locale::Zh), dedup: true, ..Default::default() };
+        assert_eq!(roundtrip(&files, options), owned(&files));
+    }
+
+    #[test]
+    fn safe_join_rejects_escapes() {
+        let root = Path::new("out");
+        assert_eq!(safe_join(root, "src/./a.rs"), Some(root.join("src").join("a.rs")));
+        assert_eq!(safe_join(root, "../a.rs"), None);
+        assert_eq!(safe_join(root, "src/../../a.rs"), None);
+        assert_eq!(safe_join(root, "/etc/passwd"), None);
+        assert_eq!(safe_join(root, "."), None);
+    }
+}
@@ -0,0 +1,406 @@
+use globset::{Glob, GlobBuilder, GlobMatcher, GlobSet, GlobSetBuilder};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::DirEntry;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::config::Config;
+use crate::encoding::{self, Binary};
+use crate::pathname;
+use crate::redact;
+use crate::stats::Skip;
+
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+/// 项目内的忽略文件，gitignore 语法。各级目录中的都会生效（由遍历器读取）；
+/// 根目录中的 `!pattern` 还能把内置规则忽略的目录、文件名与扩展名重新收录
+pub const IGNORE_FILE_NAME: &str = ".code2mdignore";
+
+// --- 忽略配置 ---
+fn get_ignore_dirs() -> &'static HashSet<&'static str> {
+    static DIRS: OnceLock<HashSet<&'static str>> = OnceLock::new();
+    DIRS.get_or_init(|| {
+        HashSet::from([
+            ".git", ".idea", ".vscode", ".vs", "__pycache__", "node_modules", 
+            "venv", ".venv", "env", "dist", "build", "target", "out", 
+            "bin", "obj", "debug", "release", 
+            ".gradle", "captures", "gradle", ".DS_Store", "coverage", ".next", ".nuxt"
+        ])
+    })
+}
+
+fn get_ignore_filenames() -> &'static HashSet<&'static str> {
+    static FILES: OnceLock<HashSet<&'static str>> = OnceLock::new();
+    FILES.get_or_init(|| {
+        HashSet::from([
+            "gradlew", "gradlew.bat", "mvnw", "mvnw.cmd",
+            "local.properties", "thumbs.db", "desktop.ini", 
+            "package-lock.json", "yarn.lock", "pnpm-lock.yaml", "cargo.lock", "poetry.lock"
+        ])
+    })
+}
+
+fn get_ignore_extensions() -> &'static HashSet<&'static str> {
+    static EXTS: OnceLock<HashSet<&'static str>> = OnceLock::new();
+    EXTS.get_or_init(|| {
+        HashSet::from([
+            // 媒体文件
+            ".png", ".jpg", ".jpeg", ".gif", ".bmp", ".ico", ".svg", ".webp", ".tiff",
+            ".mp3", ".mp4", ".wav", ".avi", ".mov",
+            // 二进制/压缩包
+            ".exe", ".dll", ".so", ".dylib", ".bin", ".apk", ".aab", ".jar", ".war",
+            ".zip", ".tar", ".gz", ".7z", ".rar", ".iso", ".cab",
+            // 编译中间产物
+            ".pyc", ".class", ".o", ".obj", ".pdb", ".suo",
+            ".db", ".sqlite", ".sqlite3", ".lock", ".log",
+            // 默认忽略 md 文件，避免收录以前生成的文档；README 例外，--include-md 可全部收录
+            ".md"
+        ])
+    })
+}
+
+/// 合并内置忽略集合与项目配置后的过滤规则
+pub struct Filter {
+    root: PathBuf,
+    exclude: ExcludeRules,
+    include: GlobSet,
+    /// 输入路径本身是通配模式时，只收录与之匹配的文件
+    pattern: Option<GlobMatcher>,
+    ignore_dirs: HashSet<String>,
+    ignore_files: HashSet<String>,
+    ignore_extensions: HashSet<String>,
+    max_file_size: u64,
+    /// --extract-pdf 时 PDF 的大小上限：给出了 --max-file-size 时与之相同，否则为 MAX_PDF_SIZE
+    pdf_size_limit: u64,
+    skip_secrets: bool,
+    /// 不再跳过 . 开头的目录（.git 等内置忽略目录除外）
+    hidden: bool,
+    /// .md 只因内置规则被忽略时，仍收录 README.md
+    keep_readme: bool,
+    /// 收录 PDF 并提取文字，不做文本检测
+    extract_pdf: bool,
+    /// 根目录的 .code2mdignore
+    overrides: Gitignore,
+}
+
+impl Filter {
+    pub fn new(root: &Path, config: &Config) -> io::Result<Filter> {
+        // 内置集合先去掉配置中要求不忽略的项，再加入配置中额外忽略的项
+        let defaults = config.default_ignores.unwrap_or(true);
+        let builtin = |set: &HashSet<&'static str>, removed: &[String], normalize: fn(&str) -> String| -> HashSet<String> {
+            if !defaults {
+                return HashSet::new();
+            }
+            let removed: HashSet<String> = removed.iter().map(|s| normalize(s)).collect();
+            set.iter().map(|s| s.to_string()).filter(|s| !removed.contains(s)).collect()
+        };
+
+        let mut ignore_dirs = builtin(get_ignore_dirs(), &config.unignore_dirs, |s| s.to_string());
+        ignore_dirs.extend(config.ignore_dirs.iter().cloned());
+
+        let mut ignore_files = builtin(get_ignore_filenames(), &config.unignore_files, |s| s.to_lowercase());
+        ignore_files.extend(config.ignore_files.iter().map(|s| s.to_lowercase()));
+
+        let mut ignore_extensions = builtin(get_ignore_extensions(), &config.unignore_extensions, normalize_extension);
+        let user_extensions: Vec<String> = config.ignore_extensions.iter().map(|s| normalize_extension(s)).collect();
+        let keep_readme = !user_extensions.iter().any(|e| e == ".md");
+        if config.include_md.unwrap_or(false) {
+            ignore_extensions.remove(".md");
+        }
+        ignore_extensions.extend(user_extensions);
+
+        Ok(Filter {
+            root: root.to_path_buf(),
+            exclude: ExcludeRules::new(&config.exclude)?,
+            include: build_globset(&config.include)?,
+            pattern: None,
+            ignore_dirs,
+            ignore_files,
+            ignore_extensions,
+            // 0 表示不限大小；较大的文件在写出时流式读取，不会全部留在内存中
+            max_file_size: match config.max_file_size {
+                Some(0) => u64::MAX,
+                Some(size) => size,
+                None => DEFAULT_MAX_FILE_SIZE,
+            },
+            pdf_size_limit: match config.max_file_size {
+                Some(0) => u64::MAX,
+                Some(size) => size,
+                None => MAX_PDF_SIZE,
+            },
+            skip_secrets: config.skip_secrets.unwrap_or(false),
+            hidden: config.hidden.unwrap_or(false),
+            keep_readme,
+            extract_pdf: config.extract_pdf.unwrap_or(false),
+            overrides: load_overrides(root)?,
+        })
+    }
+
+    /// 只收录相对根目录的路径匹配 pattern 的文件；* 与 ? 不跨越目录，** 可匹配多级目录
+    pub fn restrict_to(&mut self, pattern: &str) -> io::Result<()> {
+        let glob = GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+        self.pattern = Some(glob.compile_matcher());
+        Ok(())
+    }
+
+    pub fn is_hidden_or_ignored(&self, entry: &DirEntry) -> bool {
+        let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+        self.name_rejection(entry.path(), is_dir).is_some()
+    }
+
+    /// 遍历时名称层面的检查：目录或文件因何被剪掉，None 为通过
+    pub fn name_rejection(&self, path: &Path, is_dir: bool) -> Option<String> {
+        let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+
+        // .code2mdignore 重新收录的只绕过内置规则，排除模式照常生效
+        if !self.is_whitelisted(path, is_dir) {
+            if is_dir && self.ignore_dirs.contains(name.as_ref()) {
+                return Some(format!("目录名 {} 在忽略列表中", name));
+            }
+            if is_dir && self.is_ignored_dir_name(&name) {
+                return Some(format!("目录名 {} 以 . 开头（--hidden 可收录）", name));
+            }
+            if !is_dir && self.ignore_files.contains(&name.to_lowercase()) {
+                return Some(format!("文件名 {} 在忽略列表中", name));
+            }
+        }
+        let (matched, index) = self.exclude_match(path, is_dir)?;
+        if !self.exclude.excludes(index) {
+            return None;
+        }
+        // 被排除的目录下若可能有 `!pattern` 重新收录的路径，则继续进入，逐个判断
+        if is_dir && self.exclude.may_reinclude_under(&self.relative(path)) {
+            return None;
+        }
+        Some(format!("{} 匹配排除模式 {}", matched, self.exclude.pattern(index)))
+    }
+
+    /// 不经遍历、直接给出的文件（如 git ls-files 的结果）所适用的名称层面检查：
+    /// 文件名忽略规则，以及排除模式（文件本身未匹配时沿用父目录的结果）
+    pub fn rejects_listed(&self, path: &Path) -> bool {
+        // 遍历器不参与时由这里应用根目录的 .code2mdignore
+        let mut whitelisted = false;
+        if path.starts_with(&self.root) {
+            match self.overrides.matched_path_or_any_parents(path, false) {
+                ignore::Match::Ignore(_) => return true,
+                ignore::Match::Whitelist(_) => whitelisted = true,
+                ignore::Match::None => {}
+            }
+        }
+        let name = path.file_name().map(|n| n.to_string_lossy().to_lowercase()).unwrap_or_default();
+        if !whitelisted && self.ignore_files.contains(&name) { return true; }
+        self.is_excluded(path, false)
+    }
+
+    fn is_ignored_dir_name(&self, name: &str) -> bool {
+        (!self.hidden && name.starts_with('.') && name.len() > 1 && name != ".github") || self.ignore_dirs.contains(name)
+    }
+
+    /// 路径是否位于被忽略的目录之下，供监视模式过滤事件
+    pub fn is_inside_ignored_dir(&self, path: &Path) -> bool {
+        let rel = path.strip_prefix(&self.root).unwrap_or(path);
+        let parent = rel.parent().unwrap_or(Path::new(""));
+        parent.components().any(|c| self.is_ignored_dir_name(&c.as_os_str().to_string_lossy()))
+    }
+
+    /// 由路径本身或最近一个有模式匹配的父目录决定是否排除
+    fn is_excluded(&self, path: &Path, is_dir: bool) -> bool {
+        self.exclude_match(path, is_dir).is_some_and(|(_, index)| self.exclude.excludes(index))
+    }
+
+    /// 路径本身或最近一个有模式匹配的父目录，及其最后匹配的模式下标
+    fn exclude_match(&self, path: &Path, is_dir: bool) -> Option<(String, usize)> {
+        if self.exclude.is_empty() { return None; }
+        let rel = self.relative(path);
+        let mut candidates = std::iter::once((rel.as_str(), is_dir))
+            .chain(ancestors(&rel).map(|dir| (dir, true)));
+        candidates.find_map(|(p, dir)| self.exclude.decide(p, dir).map(|index| (p.to_string(), index)))
+    }
+
+    /// 是否被 .code2mdignore 中的 `!pattern` 重新收录
+    fn is_whitelisted(&self, path: &Path, is_dir: bool) -> bool {
+        path.starts_with(&self.root) && self.overrides.matched(path, is_dir).is_whitelist()
+    }
+
+    /// 相对于扫描根目录、以 / 分隔的路径
+    fn relative(&self, path: &Path) -> String {
+        let rel = path.strip_prefix(&self.root).unwrap_or(path);
+        rel.to_string_lossy().replace('\\', "/")
+    }
+
+    /// 文件的大小上限；--extract-pdf 且未给出 --max-file-size 时 PDF 另有默认上限
+    pub fn size_limit(&self, path: &Path) -> u64 {
+        if self.extract_pdf && is_pdf(path) { self.pdf_size_limit } else { self.max_file_size }
+    }
+
+    /// 是否跳过疑似密钥文件，内容层面的检查由读取文件时完成
+    pub fn skip_secrets(&self) -> bool {
+        self.skip_secrets
+    }
+
+    /// 包含模式、扩展名、大小与文本检测；不收录时给出原因
+    pub fn check_file(&self, path: &Path) -> Result<(), Skip> {
+        if self.skip_secrets {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            if redact::is_secret_file_name(&name) { return Err(Skip::Secret); }
+        }
+
+        // 包含模式只作用于文件，目录照常遍历以便找到深层匹配
+        // 直接以路径匹配，不是合法 UTF-8 的名称也按原始字节比较
+        let rel = path.strip_prefix(&self.root).unwrap_or(path);
+        if !self.include.is_empty() && !self.include.is_match(rel) {
+            return Err(Skip::NotIncluded);
+        }
+        if self.pattern.as_ref().is_some_and(|p| !p.is_match(rel)) {
+            return Err(Skip::NotIncluded);
+        }
+
+        if let Some(ext) = path.extension() {
+            let ext_str = format!(".{}", ext.to_string_lossy().to_lowercase());
+            if self.ignore_extensions.contains(&ext_str)
+                && !(self.keep_readme && is_readme(path))
+                && !self.is_whitelisted(path, false)
+            {
+                return Err(Skip::Extension);
+            }
+        }
+
+        let path = pathname::extended(path);
+        if let Ok(meta) = path.metadata() {
+            if meta.len() > self.size_limit(&path) { return Err(Skip::TooLarge); }
+        }
+        // PDF 的文字在读取时提取，不做文本检测
+        if self.extract_pdf && is_pdf(&path) {
+            return Ok(());
+        }
+
+        // 打不开的文件如实记为无法读取，而不是当作二进制文件
+        match is_text_file(&path) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(Skip::Binary),
+            Err(_) => Err(Skip::Unreadable),
+        }
+    }
+}
+
+/// 按顺序排列的排除模式，`!` 开头的为例外；与 gitignore 一样以最后一个匹配的模式为准
+struct ExcludeRules {
+    set: GlobSet,
+    /// 原样的模式，供说明时引用
+    patterns: Vec<String>,
+    negated: Vec<bool>,
+    /// 各个例外模式中第一个通配符之前的字面前缀，用于判断被排除的目录能否剪掉
+    reinclude_prefixes: Vec<String>,
+}
+
+impl ExcludeRules {
+    fn new(patterns: &[String]) -> io::Result<ExcludeRules> {
+        let mut globs = Vec::with_capacity(patterns.len());
+        let mut negated = Vec::with_capacity(patterns.len());
+        let mut reinclude_prefixes = Vec::new();
+        for pattern in patterns {
+            match pattern.strip_prefix('!') {
+                Some(rest) => {
+                    let literal = rest.find(['*', '?', '[', '{']).map_or(rest, |i| &rest[..i]);
+                    reinclude_prefixes.push(literal.to_string());
+                    globs.push(rest.to_string());
+                    negated.push(true);
+                }
+                None => {
+                    globs.push(pattern.clone());
+                    negated.push(false);
+                }
+            }
+        }
+        Ok(ExcludeRules { set: build_globset(&globs)?, patterns: patterns.to_vec(), negated, reinclude_prefixes })
+    }
+
+    fn is_empty(&self) -> bool {
+        self.set.is_empty()
+    }
+
+    /// 决定路径本身去留的模式（最后一个匹配的）下标，None 为没有模式匹配
+    fn decide(&self, rel: &str, is_dir: bool) -> Option<usize> {
+        if rel.is_empty() { return None; }
+        let mut matched = self.set.matches(rel);
+        // 目录额外以 "dir/" 形式匹配，使 "docs/**" 能直接剪掉整个目录
+        if is_dir {
+            matched.extend(self.set.matches(format!("{}/", rel)));
+        }
+        matched.into_iter().max()
+    }
+
+    /// 该模式是排除而不是例外
+    fn excludes(&self, index: usize) -> bool {
+        !self.negated[index]
+    }
+
+    fn pattern(&self, index: usize) -> &str {
+        &self.patterns[index]
+    }
+
+    /// 目录 rel 之下是否可能有路径被例外模式重新收录
+    fn may_reinclude_under(&self, rel: &str) -> bool {
+        let dir = format!("{}/", rel);
+        self.reinclude_prefixes.iter().any(|p| p.starts_with(&dir) || dir.starts_with(p.as_str()))
+    }
+}
+
+/// rel 的各级父目录，由近及远，不含根目录本身
+fn ancestors(rel: &str) -> impl Iterator<Item = &str> {
+    rel.char_indices().rev().filter(|&(_, c)| c == '/').map(move |(i, _)| &rel[..i])
+}
+
+fn load_overrides(root: &Path) -> io::Result<Gitignore> {
+    let path = root.join(IGNORE_FILE_NAME);
+    if !path.is_file() {
+        return Ok(Gitignore::empty());
+    }
+    let mut builder = GitignoreBuilder::new(root);
+    if let Some(e) = builder.add(&path) {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), e)));
+    }
+    builder.build().map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), e)))
+}
+
+fn build_globset(patterns: &[String]) -> io::Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = Glob::new(pattern)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+        builder.add(glob);
+    }
+    builder.build().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))
+}
+
+/// 未给出 --max-file-size 时 --extract-pdf 收录的 PDF 大小上限；提取出的文字通常远小于文件本身
+const MAX_PDF_SIZE: u64 = 10 * 1024 * 1024;
+
+pub fn is_pdf(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e.eq_ignore_ascii_case("pdf"))
+}
+
+fn is_readme(path: &Path) -> bool {
+    path.file_name().is_some_and(|n| n.eq_ignore_ascii_case("readme.md"))
+}
+
+fn normalize_extension(ext: &str) -> String {
+    format!(".{}", ext.trim_start_matches('.').to_lowercase())
+}
+
+fn is_text_file(path: &Path) -> io::Result<bool> {
+    Ok(binary_kind(path)?.is_none())
+}
+
+/// 按开头的样本判断文件是否为二进制及其原因，None 表示文本
+pub fn binary_kind(path: &Path) -> io::Result<Option<Binary>> {
+    let mut buffer = vec![0; encoding::SAMPLE_SIZE];
+    let n = encoding::read_full(&mut File::open(path)?, &mut buffer)?;
+    Ok(encoding::sniff_binary(&buffer[..n], n < buffer.len()))
+}
@@ -0,0 +1,51 @@
+use std::io::{self, Write};
+
+use crate::render::{self, FileEntry, RenderOptions};
+
+/// AsciiDoc：`== File:` 小节加 `[source,lang]` 代码块，目录交给 `:toc:` 生成
+pub fn write(writer: &mut impl Write, entries: &[FileEntry], opts: &RenderOptions) -> io::Result<()> {
+    let text = opts.locale.strings();
+    writeln!(writer, "= {}", opts.title)?;
+    if opts.toc {
+        writeln!(writer, ":toc:")?;
+    }
+    writeln!(writer)?;
+    if let Some(preamble) = opts.preamble {
+        writeln!(writer, "{}\n", preamble)?;
+    }
+
+    for entry in entries {
+        writeln!(writer, "== {}{}\n", text.file_prefix, entry.marked_path())?;
+        if let Some(original) = &entry.duplicate_of {
+            writeln!(writer, "{}\n", text.identical_to.replace("{path}", &format!("`{}`", original)))?;
+            continue;
+        }
+        if let Some(note) = &entry.placeholder {
+            writeln!(writer, "{}.\n", note)?;
+            continue;
+        }
+        let content = render::display_content(entry, opts);
+        let delimiter = delimiter_for(&content);
+        if entry.language.is_empty() {
+            writeln!(writer, "[source]")?;
+        } else {
+            writeln!(writer, "[source,{}]", entry.language)?;
+        }
+        writeln!(writer, "{}", delimiter)?;
+        writeln!(writer, "{}", content.trim_end_matches('\n'))?;
+        writeln!(writer, "{}\n", delimiter)?;
+    }
+    Ok(())
+}
+
+/// 内容中若有整行的 `----`，改用更长的分隔符，避免代码块被提前闭合
+fn delimiter_for(content: &str) -> String {
+    let longest = content
+        .lines()
+        .map(str::trim_end)
+        .filter(|l| l.len() >= 4 && l.bytes().all(|b| b == b'-'))
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    "-".repeat(longest.max(3) + 1)
+}
@@ -0,0 +1,78 @@
+use std::io::{self, Write};
+
+use super::html::escape;
+use super::xml_safe;
+use crate::render::{FileEntry, RenderOptions};
+
+/// 代码宏认识的语言名称与代码块语言标识不同的部分
+const LANGUAGES: &[(&str, &str)] = &[
+    ("python", "py"),
+    ("javascript", "js"),
+    ("csharp", "c#"),
+    ("erlang", "erl"),
+    ("objectivec", "objective-c"),
+    ("yaml", "yml"),
+    ("zsh", "bash"),
+    ("fish", "bash"),
+    ("sh", "bash"),
+];
+
+/// 代码宏直接支持的其余语言；不在其中的语言写成 text，避免 Confluence 报错或按 Java 高亮
+const SUPPORTED: &[&str] = &[
+    "actionscript3", "applescript", "bash", "c", "clojure", "coffeescript", "cpp", "css", "dart", "delphi", "diff",
+    "elixir", "go", "graphql", "groovy", "haskell", "html", "java", "json", "jsx", "julia", "kotlin", "lua",
+    "ocaml", "perl", "php", "powershell", "r", "ruby", "rust", "sass", "scala", "sql", "swift", "tsx", "typescript",
+    "xml",
+];
+
+/// Confluence 存储格式（XHTML）：`File:` 二级标题加代码宏，可直接粘贴或通过 API 写入页面
+pub fn write(writer: &mut impl Write, entries: &[FileEntry], opts: &RenderOptions) -> io::Result<()> {
+    let text = opts.locale.strings();
+    writeln!(writer, "<h1>{}</h1>", escape(opts.title))?;
+    if let Some(preamble) = opts.preamble {
+        writeln!(writer, "<p>{}</p>", escape(preamble).replace('\n', "<br />"))?;
+    }
+    if opts.toc {
+        writeln!(writer, "<ac:structured-macro ac:name=\"toc\" />")?;
+    }
+
+    for entry in entries {
+        writeln!(writer, "<h2>{}{}</h2>", text.file_prefix, escape(&entry.marked_path()))?;
+        if let Some(original) = &entry.duplicate_of {
+            writeln!(writer, "<p>{}</p>", text.identical_to.replace("{path}", &format!("<code>{}</code>", escape(original))))?;
+            continue;
+        }
+        if let Some(note) = &entry.placeholder {
+            writeln!(writer, "<p>{}.</p>", escape(note))?;
+            continue;
+        }
+        write!(writer, "<ac:structured-macro ac:name=\"code\">")?;
+        write!(writer, "{}", parameter("language", language(&entry.language)))?;
+        if opts.line_numbers {
+            write!(writer, "{}", parameter("linenumbers", "true"))?;
+        }
+        if opts.collapsible {
+            write!(writer, "{}", parameter("collapse", "true"))?;
+        }
+        writeln!(writer, "<ac:plain-text-body>{}</ac:plain-text-body></ac:structured-macro>", cdata(&entry.content))?;
+    }
+    Ok(())
+}
+
+fn parameter(name: &str, value: &str) -> String {
+    format!("<ac:parameter ac:name=\"{}\">{}</ac:parameter>", name, escape(value))
+}
+
+fn language(fence: &str) -> &str {
+    match LANGUAGES.iter().find(|(from, _)| *from == fence) {
+        Some((_, to)) => to,
+        None if SUPPORTED.contains(&fence) => fence,
+        None => "text",
+    }
+}
+
+/// 内容中的 `]]>` 拆到两段 CDATA 中；XML 不允许的控制字符换成 U+FFFD
+fn cdata(content: &str) -> String {
+    let content = xml_safe(content.trim_end_matches('\n'));
+    format!("<![CDATA[{}]]>", content.replace("]]>", "]]]]><![CDATA[>"))
+}
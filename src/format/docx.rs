@@ -0,0 +1,179 @@
+use rayon::prelude::*;
+use std::io::{self, Cursor, Write};
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{FontStyle, Style};
+use syntect::util::LinesWithEndings;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+use super::html::{escape, theme};
+use super::xml_safe;
+use crate::lang::syntax_set;
+use crate::render::{self, FileEntry, RenderOptions};
+use crate::tree::Tree;
+
+const CONTENT_TYPES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
+  <Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>
+  <Default Extension="xml" ContentType="application/xml"/>
+  <Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/>
+  <Override PartName="/word/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.styles+xml"/>
+  <Override PartName="/word/settings.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.settings+xml"/>
+</Types>
+"#;
+
+const PACKAGE_RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+  <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/>
+</Relationships>
+"#;
+
+const DOCUMENT_RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+  <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/>
+  <Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/settings" Target="settings.xml"/>
+</Relationships>
+"#;
+
+/// 打开时提示更新域，目录才会填上页码
+const SETTINGS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:settings xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:updateFields w:val="true"/>
+</w:settings>
+"#;
+
+/// 使用 Word 内置的标题样式名，导航窗格与目录都能识别；代码段落为等宽字体、无段间距
+const STYLES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:docDefaults>
+    <w:rPrDefault><w:rPr><w:rFonts w:ascii="Calibri" w:hAnsi="Calibri" w:eastAsia="Microsoft YaHei" w:cs="Calibri"/><w:sz w:val="22"/></w:rPr></w:rPrDefault>
+    <w:pPrDefault><w:pPr><w:spacing w:after="120"/></w:pPr></w:pPrDefault>
+  </w:docDefaults>
+  <w:style w:type="paragraph" w:default="1" w:styleId="Normal"><w:name w:val="Normal"/><w:qFormat/></w:style>
+  <w:style w:type="paragraph" w:styleId="Title"><w:name w:val="Title"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/>
+    <w:pPr><w:spacing w:after="240"/></w:pPr><w:rPr><w:sz w:val="48"/></w:rPr></w:style>
+  <w:style w:type="paragraph" w:styleId="Heading1"><w:name w:val="heading 1"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/>
+    <w:pPr><w:keepNext/><w:spacing w:before="360" w:after="120"/><w:outlineLvl w:val="0"/></w:pPr><w:rPr><w:b/><w:sz w:val="28"/></w:rPr></w:style>
+  <w:style w:type="paragraph" w:styleId="TOCHeading"><w:name w:val="TOC Heading"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/>
+    <w:pPr><w:keepNext/><w:spacing w:before="240" w:after="120"/></w:pPr><w:rPr><w:b/><w:sz w:val="28"/></w:rPr></w:style>
+  <w:style w:type="paragraph" w:customStyle="1" w:styleId="Code"><w:name w:val="Code"/><w:basedOn w:val="Normal"/><w:qFormat/>
+    <w:pPr><w:spacing w:after="0" w:line="240" w:lineRule="auto"/><w:shd w:val="clear" w:color="auto" w:fill="F6F8FA"/></w:pPr>
+    <w:rPr><w:rFonts w:ascii="Consolas" w:hAnsi="Consolas" w:eastAsia="Microsoft YaHei" w:cs="Consolas"/><w:sz w:val="18"/></w:rPr></w:style>
+  <w:style w:type="paragraph" w:customStyle="1" w:styleId="Note"><w:name w:val="Note"/><w:basedOn w:val="Normal"/><w:qFormat/>
+    <w:rPr><w:i/><w:color w:val="57606A"/></w:rPr></w:style>
+</w:styles>
+"#;
+
+/// Word 文档：标题、目录域、目录树，之后每个文件一个一级标题，代码逐行写成带语法高亮的等宽段落。
+/// 与 EPUB 一样先在内存中打包
+pub fn write(writer: &mut impl Write, entries: &[FileEntry], opts: &RenderOptions) -> io::Result<()> {
+    let text = opts.locale.strings();
+    let bodies: Vec<String> = entries
+        .par_iter()
+        .map(|e| match (&e.duplicate_of, &e.placeholder) {
+            (Some(original), _) => paragraph("Note", &run(&text.identical_to.replace("{path}", original), None)),
+            (None, Some(note)) => paragraph("Note", &run(&format!("{}.", note), None)),
+            (None, None) => highlight(e, opts),
+        })
+        .collect();
+
+    let mut body = paragraph("Title", &run(opts.title, None));
+    if let Some(preamble) = opts.preamble {
+        for line in preamble.lines() {
+            body.push_str(&paragraph("Normal", &run(line, None)));
+        }
+    }
+    if opts.toc {
+        body.push_str(&paragraph("TOCHeading", &run(text.toc, None)));
+        body.push_str(concat!(
+            "<w:p><w:r><w:fldChar w:fldCharType=\"begin\" w:dirty=\"true\"/></w:r>",
+            "<w:r><w:instrText xml:space=\"preserve\"> TOC \\o \"1-1\" \\h \\z \\u </w:instrText></w:r>",
+            "<w:r><w:fldChar w:fldCharType=\"separate\"/></w:r>",
+            "<w:r><w:t>Update the field to build the table of contents.</w:t></w:r>",
+            "<w:r><w:fldChar w:fldCharType=\"end\"/></w:r></w:p>\n",
+        ));
+    }
+    if opts.tree {
+        let paths: Vec<String> = entries.iter().map(FileEntry::display_path).collect();
+        let tree = Tree::from_paths(paths.iter().map(String::as_str));
+        for line in tree.render(opts.project_name).lines() {
+            body.push_str(&paragraph("Code", &run(line, None)));
+        }
+    }
+    for (entry, content) in entries.iter().zip(&bodies) {
+        body.push_str(&paragraph("Heading1", &run(&format!("{}{}", text.file_prefix, entry.marked_path()), None)));
+        body.push_str(content);
+    }
+    let document = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<w:document xmlns:w=\"http://schemas.openxmlformats.org/wordprocessingml/2006/main\">\n<w:body>\n{}<w:sectPr><w:pgMar w:top=\"1134\" w:right=\"1134\" w:bottom=\"1134\" w:left=\"1134\" w:header=\"708\" w:footer=\"708\" w:gutter=\"0\"/></w:sectPr>\n</w:body>\n</w:document>\n",
+        body
+    );
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let mut add = |name: &str, content: &str| -> io::Result<()> {
+        zip.start_file(name, SimpleFileOptions::default()).map_err(io::Error::other)?;
+        zip.write_all(content.as_bytes())
+    };
+    add("[Content_Types].xml", CONTENT_TYPES)?;
+    add("_rels/.rels", PACKAGE_RELS)?;
+    add("word/_rels/document.xml.rels", DOCUMENT_RELS)?;
+    add("word/styles.xml", STYLES)?;
+    add("word/settings.xml", SETTINGS)?;
+    add("word/document.xml", &document)?;
+    let buffer = zip.finish().map_err(io::Error::other)?.into_inner();
+    writer.write_all(&buffer)
+}
+
+fn paragraph(style: &str, runs: &str) -> String {
+    format!("<w:p><w:pPr><w:pStyle w:val=\"{}\"/></w:pPr>{}</w:p>\n", style, runs)
+}
+
+/// 一段文字；制表符写成 <w:tab/>，style 给出高亮的颜色与粗斜体
+fn run(text: &str, style: Option<Style>) -> String {
+    let mut properties = String::new();
+    if let Some(style) = style {
+        if style.font_style.contains(FontStyle::BOLD) {
+            properties.push_str("<w:b/>");
+        }
+        if style.font_style.contains(FontStyle::ITALIC) {
+            properties.push_str("<w:i/>");
+        }
+        let c = style.foreground;
+        properties.push_str(&format!("<w:color w:val=\"{:02X}{:02X}{:02X}\"/>", c.r, c.g, c.b));
+    }
+    let mut out = format!("<w:r>{}", if properties.is_empty() { String::new() } else { format!("<w:rPr>{}</w:rPr>", properties) });
+    for (i, part) in xml_safe(text).split('\t').enumerate() {
+        if i > 0 {
+            out.push_str("<w:tab/>");
+        }
+        if !part.is_empty() {
+            out.push_str(&format!("<w:t xml:space=\"preserve\">{}</w:t>", escape(part)));
+        }
+    }
+    out.push_str("</w:r>");
+    out
+}
+
+/// 每行一个 Code 段落；没有对应语法的文件按纯文本高亮，即只有默认颜色
+fn highlight(entry: &FileEntry, opts: &RenderOptions) -> String {
+    let content = render::display_content(entry, opts);
+    let set = syntax_set();
+    let syntax = set
+        .find_syntax_by_extension(&entry.extension)
+        .unwrap_or_else(|| set.find_syntax_plain_text());
+    let mut highlighter = HighlightLines::new(syntax, theme());
+    let mut out = String::new();
+    for line in LinesWithEndings::from(content.trim_end_matches('\n')) {
+        let runs = match highlighter.highlight_line(line, set) {
+            Ok(regions) => regions
+                .iter()
+                .map(|(style, text)| (style, text.trim_end_matches(['\n', '\r'])))
+                .filter(|(_, text)| !text.is_empty())
+                .map(|(style, text)| run(text, Some(*style)))
+                .collect(),
+            Err(_) => run(line.trim_end_matches(['\n', '\r']), None),
+        };
+        out.push_str(&paragraph("Code", &runs));
+    }
+    out
+}
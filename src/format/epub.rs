@@ -0,0 +1,162 @@
+use std::io::{self, Cursor, Write};
+use std::time::SystemTime;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use super::html::escape;
+use super::xml_safe;
+use crate::locale::Locale;
+use crate::render::{format_iso, FileEntry, RenderOptions};
+use crate::sha256;
+use crate::tree::Tree;
+
+const STYLE: &str = "body { margin: 0 4%; }
+h1, h2 { font-family: sans-serif; }
+h2 { font-size: 1em; word-break: break-all; }
+pre { font-family: monospace; font-size: 0.75em; white-space: pre-wrap; word-wrap: break-word; }
+p.note { font-style: italic; }
+";
+
+const CONTAINER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>
+"#;
+
+/// EPUB 3：标题页（说明文字与目录树）之后每个文件一章，附带导航目录与兼容旧阅读器的 toc.ncx。
+/// zip 需要可回退的 writer，先在内存中打包再整体写出，因此也能写到标准输出
+pub fn write(writer: &mut impl Write, entries: &[FileEntry], opts: &RenderOptions) -> io::Result<()> {
+    let chapters: Vec<String> = (1..=entries.len()).map(|i| format!("file-{:04}.xhtml", i)).collect();
+    let paths: Vec<String> = entries.iter().map(FileEntry::display_path).collect();
+    let identifier = identifier(opts.title, &paths);
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    // 规范要求 mimetype 为第一项且不压缩
+    zip.start_file("mimetype", SimpleFileOptions::default().compression_method(CompressionMethod::Stored)).map_err(io::Error::other)?;
+    zip.write_all(b"application/epub+zip")?;
+    let mut add = |name: &str, content: &str| -> io::Result<()> {
+        zip.start_file(name, SimpleFileOptions::default()).map_err(io::Error::other)?;
+        zip.write_all(content.as_bytes())
+    };
+    add("META-INF/container.xml", CONTAINER)?;
+    add("OEBPS/style.css", STYLE)?;
+    add("OEBPS/content.opf", &package(opts, &identifier, &chapters))?;
+    add("OEBPS/nav.xhtml", &navigation(&paths, &chapters, opts.locale))?;
+    add("OEBPS/toc.ncx", &ncx(opts.title, &identifier, &paths, &chapters))?;
+    add("OEBPS/title.xhtml", &title_page(&paths, opts))?;
+    for (i, entry) in entries.iter().enumerate() {
+        add(&format!("OEBPS/{}", chapters[i]), &chapter(entry, entries, &chapters, opts))?;
+    }
+    let buffer = zip.finish().map_err(io::Error::other)?.into_inner();
+    writer.write_all(&buffer)
+}
+
+/// 由标题与文件列表得出的稳定标识，内容不变时重新生成的电子书被阅读器视为同一本
+fn identifier(title: &str, paths: &[String]) -> String {
+    let hash = sha256::hex(format!("{}\n{}", title, paths.join("\n")).as_bytes());
+    format!("urn:uuid:{}-{}-{}-{}-{}", &hash[..8], &hash[8..12], &hash[12..16], &hash[16..20], &hash[20..32])
+}
+
+fn xhtml(title: &str, body: &str, nav: bool, locale: Locale) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<!DOCTYPE html>\n<html xmlns=\"http://www.w3.org/1999/xhtml\"{} lang=\"{}\" xml:lang=\"{}\">\n<head>\n<meta charset=\"utf-8\"/>\n<title>{}</title>\n<link rel=\"stylesheet\" type=\"text/css\" href=\"style.css\"/>\n</head>\n<body>\n{}</body>\n</html>\n",
+        if nav { " xmlns:epub=\"http://www.idpf.org/2007/ops\"" } else { "" },
+        locale.tag(),
+        locale.tag(),
+        escape(&xml_safe(title)),
+        body
+    )
+}
+
+fn package(opts: &RenderOptions, identifier: &str, chapters: &[String]) -> String {
+    let mut manifest = String::from(concat!(
+        "    <item id=\"nav\" href=\"nav.xhtml\" media-type=\"application/xhtml+xml\" properties=\"nav\"/>\n",
+        "    <item id=\"ncx\" href=\"toc.ncx\" media-type=\"application/x-dtbncx+xml\"/>\n",
+        "    <item id=\"style\" href=\"style.css\" media-type=\"text/css\"/>\n",
+        "    <item id=\"title\" href=\"title.xhtml\" media-type=\"application/xhtml+xml\"/>\n",
+    ));
+    let mut spine = String::from("    <itemref idref=\"title\"/>\n");
+    if opts.toc {
+        spine.push_str("    <itemref idref=\"nav\"/>\n");
+    }
+    for (i, chapter) in chapters.iter().enumerate() {
+        manifest.push_str(&format!("    <item id=\"f{}\" href=\"{}\" media-type=\"application/xhtml+xml\"/>\n", i + 1, chapter));
+        spine.push_str(&format!("    <itemref idref=\"f{}\"/>\n", i + 1));
+    }
+    let modified = format_iso(SystemTime::now());
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<package xmlns=\"http://www.idpf.org/2007/opf\" version=\"3.0\" unique-identifier=\"id\">\n  <metadata xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n    <dc:identifier id=\"id\">{}</dc:identifier>\n    <dc:title>{}</dc:title>\n    <dc:language>{}</dc:language>\n    <dc:creator>code2md {}</dc:creator>\n    <meta property=\"dcterms:modified\">{}</meta>\n  </metadata>\n  <manifest>\n{}  </manifest>\n  <spine toc=\"ncx\">\n{}  </spine>\n</package>\n",
+        identifier,
+        escape(&xml_safe(opts.title)),
+        opts.locale.tag(),
+        env!("CARGO_PKG_VERSION"),
+        modified,
+        manifest,
+        spine
+    )
+}
+
+/// EPUB 3 的导航文档，阅读器据此生成目录
+fn navigation(paths: &[String], chapters: &[String], locale: Locale) -> String {
+    let heading = locale.strings().toc;
+    let mut body = format!("<nav epub:type=\"toc\" id=\"toc\">\n<h1>{}</h1>\n<ol>\n", heading);
+    for (path, chapter) in paths.iter().zip(chapters) {
+        body.push_str(&format!("<li><a href=\"{}\">{}</a></li>\n", chapter, escape(&xml_safe(path))));
+    }
+    body.push_str("</ol>\n</nav>\n");
+    xhtml(heading, &body, true, locale)
+}
+
+/// EPUB 2 的目录，部分电子阅读器只认它
+fn ncx(title: &str, identifier: &str, paths: &[String], chapters: &[String]) -> String {
+    let mut points = String::new();
+    for (i, (path, chapter)) in paths.iter().zip(chapters).enumerate() {
+        points.push_str(&format!(
+            "    <navPoint id=\"p{}\" playOrder=\"{}\"><navLabel><text>{}</text></navLabel><content src=\"{}\"/></navPoint>\n",
+            i + 1,
+            i + 1,
+            escape(&xml_safe(path)),
+            chapter
+        ));
+    }
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<ncx xmlns=\"http://www.daisy.org/z3986/2005/ncx/\" version=\"2005-1\">\n  <head><meta name=\"dtb:uid\" content=\"{}\"/></head>\n  <docTitle><text>{}</text></docTitle>\n  <navMap>\n{}  </navMap>\n</ncx>\n",
+        identifier,
+        escape(&xml_safe(title)),
+        points
+    )
+}
+
+fn title_page(paths: &[String], opts: &RenderOptions) -> String {
+    let mut body = format!("<h1>{}</h1>\n", escape(&xml_safe(opts.title)));
+    if let Some(preamble) = opts.preamble {
+        body.push_str(&format!("<p>{}</p>\n", escape(&xml_safe(preamble)).replace('\n', "<br/>")));
+    }
+    if opts.tree {
+        let tree = Tree::from_paths(paths.iter().map(String::as_str));
+        body.push_str(&format!("<pre>{}</pre>\n", escape(&xml_safe(&tree.render(opts.project_name)))));
+    }
+    xhtml(opts.title, &body, false, opts.locale)
+}
+
+fn chapter(entry: &FileEntry, entries: &[FileEntry], chapters: &[String], opts: &RenderOptions) -> String {
+    let path = entry.marked_path();
+    let text = opts.locale.strings();
+    let mut body = format!("<h2>{}</h2>\n", escape(&xml_safe(&path)));
+    if let Some(original) = &entry.duplicate_of {
+        let link = entries.iter().position(|e| e.display_path() == *original).map(|i| &chapters[i]);
+        let name = escape(&xml_safe(original));
+        match link {
+            Some(href) => body.push_str(&format!("<p class=\"note\">{}</p>\n", text.identical_to.replace("{path}", &format!("<a href=\"{}\">{}</a>", href, name)))),
+            None => body.push_str(&format!("<p class=\"note\">{}</p>\n", text.identical_to.replace("{path}", &name))),
+        }
+    } else if let Some(note) = &entry.placeholder {
+        body.push_str(&format!("<p class=\"note\">{}.</p>\n", escape(&xml_safe(note))));
+    } else {
+        let content = crate::render::display_content(entry, opts);
+        body.push_str(&format!("<pre><code>{}</code></pre>\n", escape(&xml_safe(content.trim_end_matches('\n')))));
+    }
+    xhtml(&path, &body, false, opts.locale)
+}
@@ -0,0 +1,105 @@
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::sync::OnceLock;
+use syntect::highlighting::{Theme, ThemeSet};
+use syntect::html::highlighted_html_for_string;
+
+use crate::anchor::Slugger;
+use crate::lang::syntax_set;
+use crate::render::{self, FileEntry, RenderOptions};
+use crate::tree::Tree;
+
+pub(super) fn theme() -> &'static Theme {
+    static THEME: OnceLock<Theme> = OnceLock::new();
+    THEME.get_or_init(|| {
+        let mut themes = ThemeSet::load_defaults();
+        themes.themes.remove("InspiredGitHub").unwrap_or_default()
+    })
+}
+
+const STYLE: &str = r#"
+* { box-sizing: border-box; }
+body { margin: 0; font-family: -apple-system, "Segoe UI", "Microsoft YaHei", sans-serif; color: #24292f; }
+nav { position: fixed; top: 0; bottom: 0; left: 0; width: 300px; overflow: auto; padding: 16px; background: #f6f8fa; border-right: 1px solid #d0d7de; font-size: 13px; }
+nav ul { list-style: none; margin: 0; padding-left: 14px; }
+nav > ul { padding-left: 0; }
+nav a { color: #0969da; text-decoration: none; }
+nav a:hover { text-decoration: underline; }
+nav .dir { color: #57606a; }
+main { margin-left: 300px; padding: 24px 32px; }
+details { margin-bottom: 16px; border: 1px solid #d0d7de; border-radius: 6px; }
+summary { padding: 8px 12px; background: #f6f8fa; cursor: pointer; font-family: ui-monospace, Consolas, monospace; font-size: 14px; }
+.preamble { margin-bottom: 16px; white-space: pre-wrap; }
+.duplicate { margin: 0; padding: 12px; color: #57606a; }
+pre { margin: 0; padding: 12px; overflow: auto; font-size: 13px; line-height: 1.45; }
+"#;
+
+/// 单文件 HTML：左侧目录树，右侧可折叠的高亮代码
+pub fn write(writer: &mut impl Write, entries: &[FileEntry], opts: &RenderOptions) -> io::Result<()> {
+    let text = opts.locale.strings();
+    let mut slugger = Slugger::default();
+    let paths: Vec<String> = entries.iter().map(FileEntry::display_path).collect();
+    let ids: HashMap<&str, String> = paths
+        .iter()
+        .map(|p| (p.as_str(), slugger.slug(&format!("file-{}", p))))
+        .collect();
+
+    let bodies: Vec<String> = entries
+        .par_iter()
+        .map(|e| match (&e.duplicate_of, &e.placeholder) {
+            (Some(original), _) => format!(
+                "<p class=\"duplicate\">{}</p>",
+                text.identical_to.replace("{path}", &format!("<a href=\"#{}\">{}</a>", ids[original.as_str()], escape(original)))
+            ),
+            (None, Some(note)) => format!("<p class=\"duplicate\">{}</p>", escape(note)),
+            (None, None) => highlight(e, opts),
+        })
+        .collect();
+
+    writeln!(writer, "<!DOCTYPE html>")?;
+    writeln!(writer, "<html>\n<head>\n<meta charset=\"utf-8\">")?;
+    writeln!(writer, "<title>{}</title>", escape(opts.title))?;
+    writeln!(writer, "<style>{}</style>\n</head>\n<body>", STYLE)?;
+
+    let tree = Tree::from_paths(paths.iter().map(String::as_str));
+    writeln!(writer, "<nav>\n<strong>{}</strong>", escape(opts.title))?;
+    write!(writer, "{}", tree.render_html(&|path| format!("#{}", ids[path])))?;
+    writeln!(writer, "</nav>\n<main>")?;
+    if let Some(preamble) = opts.preamble {
+        writeln!(writer, "<div class=\"preamble\">{}</div>", escape(preamble))?;
+    }
+
+    for ((path, entry), body) in paths.iter().zip(entries).zip(&bodies) {
+        writeln!(writer, "<details open id=\"{}\">", ids[path.as_str()])?;
+        writeln!(writer, "<summary>{}</summary>", escape(&entry.marked_path()))?;
+        writeln!(writer, "{}</details>", body)?;
+    }
+
+    writeln!(writer, "</main>\n</body>\n</html>")
+}
+
+fn highlight(entry: &FileEntry, opts: &RenderOptions) -> String {
+    let content = render::display_content(entry, opts);
+    let set = syntax_set();
+    let syntax = set
+        .find_syntax_by_extension(&entry.extension)
+        .unwrap_or_else(|| set.find_syntax_plain_text());
+    highlighted_html_for_string(&content, set, syntax, theme())
+        .unwrap_or_else(|_| format!("<pre>{}</pre>", escape(&content)))
+}
+
+pub fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,47 @@
+use serde::Serialize;
+use std::io::{self, Write};
+
+use crate::render::FileEntry;
+
+#[derive(Serialize)]
+struct JsonFile<'a> {
+    /// 多根目录时标明所属根目录
+    #[serde(skip_serializing_if = "str::is_empty")]
+    root: &'a str,
+    path: &'a str,
+    /// path 中有名称被转义（不是合法 UTF-8 或含控制字符）
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    escaped_path: bool,
+    language: &'a str,
+    size: u64,
+    content: &'a str,
+    /// 内容与该文件相同，content 为空
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duplicate_of: Option<&'a str>,
+    /// 二进制或过大而未收录内容，content 为空
+    #[serde(skip_serializing_if = "Option::is_none")]
+    skipped: Option<&'a str>,
+    /// 内容的 SHA-256（--hashes）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sha256: Option<&'a str>,
+}
+
+/// 输出 `[{path, language, size, content}, ...]`
+pub fn write(writer: &mut impl Write, entries: &[FileEntry]) -> io::Result<()> {
+    let files: Vec<JsonFile> = entries
+        .iter()
+        .map(|e| JsonFile {
+            root: &e.root,
+            path: &e.rel_path,
+            escaped_path: e.escaped_path,
+            language: &e.language,
+            size: e.size,
+            content: if e.duplicate_of.is_some() { "" } else { &e.content },
+            duplicate_of: e.duplicate_of.as_deref(),
+            skipped: e.placeholder.as_deref(),
+            sha256: e.sha256.as_deref(),
+        })
+        .collect();
+    serde_json::to_writer_pretty(&mut *writer, &files)?;
+    writeln!(writer)
+}
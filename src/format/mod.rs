@@ -0,0 +1,89 @@
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::io::{self, Write};
+
+use crate::render::{self, FileEntry, RenderOptions};
+
+mod adoc;
+mod confluence;
+mod docx;
+mod epub;
+pub mod html;
+mod json;
+mod org;
+mod rst;
+mod tex;
+mod txt;
+
+/// 输出格式
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    #[default]
+    #[value(alias = "md")]
+    Markdown,
+    Json,
+    Html,
+    #[value(alias = "text")]
+    Txt,
+    #[value(alias = "asciidoc")]
+    Adoc,
+    /// Confluence 存储格式（XHTML）
+    Confluence,
+    /// Emacs Org-mode
+    Org,
+    /// reStructuredText（Sphinx）
+    Rst,
+    /// EPUB 电子书，每个文件一章
+    Epub,
+    /// Word 文档
+    Docx,
+    /// LaTeX，代码放在 listings 环境中
+    #[value(alias = "latex")]
+    Tex,
+}
+
+impl Format {
+    /// 默认输出文件的扩展名
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Markdown => "md",
+            Format::Json => "json",
+            Format::Html => "html",
+            Format::Txt => "txt",
+            Format::Adoc => "adoc",
+            Format::Confluence => "xml",
+            Format::Org => "org",
+            Format::Rst => "rst",
+            Format::Epub => "epub",
+            Format::Docx => "docx",
+            Format::Tex => "tex",
+        }
+    }
+
+    /// 输出不是文本，不能复制到剪贴板或供 check 比较
+    pub fn is_binary(self) -> bool {
+        matches!(self, Format::Epub | Format::Docx)
+    }
+}
+
+pub fn write(format: Format, writer: &mut impl Write, entries: &[FileEntry], opts: &RenderOptions) -> io::Result<()> {
+    match format {
+        Format::Markdown => render::write_markdown(writer, entries, opts),
+        Format::Json => json::write(writer, entries),
+        Format::Html => html::write(writer, entries, opts),
+        Format::Txt => txt::write(writer, entries, opts),
+        Format::Adoc => adoc::write(writer, entries, opts),
+        Format::Confluence => confluence::write(writer, entries, opts),
+        Format::Org => org::write(writer, entries, opts),
+        Format::Rst => rst::write(writer, entries, opts),
+        Format::Epub => epub::write(writer, entries, opts),
+        Format::Docx => docx::write(writer, entries, opts),
+        Format::Tex => tex::write(writer, entries, opts),
+    }
+}
+
+/// XML 1.0 不允许的控制字符（制表、换行、回车之外）换成 U+FFFD
+fn xml_safe(text: &str) -> String {
+    text.chars().map(|c| if c < ' ' && !matches!(c, '\t' | '\n' | '\r') { '\u{FFFD}' } else { c }).collect()
+}
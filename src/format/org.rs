@@ -0,0 +1,65 @@
+use std::io::{self, Write};
+
+use crate::render::{self, FileEntry, RenderOptions};
+
+/// Babel 的语言名称与代码块语言标识不同的部分
+const LANGUAGES: &[(&str, &str)] = &[
+    ("javascript", "js"),
+    ("c", "C"),
+    ("cpp", "C++"),
+    ("objectivec", "objc"),
+    ("elisp", "emacs-lisp"),
+];
+
+/// Org-mode：`* File:` 一级标题加 `#+BEGIN_SRC lang` 代码块，目录由导出选项 toc 控制
+pub fn write(writer: &mut impl Write, entries: &[FileEntry], opts: &RenderOptions) -> io::Result<()> {
+    let text = opts.locale.strings();
+    writeln!(writer, "#+TITLE: {}", opts.title)?;
+    writeln!(writer, "#+OPTIONS: toc:{}", if opts.toc { "t" } else { "nil" })?;
+    writeln!(writer)?;
+    if let Some(preamble) = opts.preamble {
+        writeln!(writer, "{}\n", preamble)?;
+    }
+
+    for entry in entries {
+        writeln!(writer, "* {}{}\n", text.file_prefix, entry.marked_path())?;
+        if let Some(original) = &entry.duplicate_of {
+            writeln!(writer, "{}\n", text.identical_to.replace("{path}", &format!("={}=", original)))?;
+            continue;
+        }
+        if let Some(note) = &entry.placeholder {
+            writeln!(writer, "{}.\n", note)?;
+            continue;
+        }
+        let content = escape_block(&render::display_content(entry, opts));
+        let (open, close) = match entry.language.as_str() {
+            "" => ("#+BEGIN_EXAMPLE".to_string(), "#+END_EXAMPLE"),
+            language => (format!("#+BEGIN_SRC {}", babel_language(language)), "#+END_SRC"),
+        };
+        writeln!(writer, "{}", open)?;
+        writeln!(writer, "{}", content.trim_end_matches('\n'))?;
+        writeln!(writer, "{}\n", close)?;
+    }
+    Ok(())
+}
+
+fn babel_language(fence: &str) -> &str {
+    LANGUAGES.iter().find(|(from, _)| *from == fence).map_or(fence, |(_, to)| to)
+}
+
+/// 以 `*` 或 `#+` 开头（可有缩进）的行前加逗号，否则会被当作标题或结束块；Org 导出与编辑时会去掉这个逗号
+fn escape_block(content: &str) -> String {
+    let mut out = String::with_capacity(content.len());
+    for line in content.split_inclusive('\n') {
+        let rest = line.trim_start_matches([' ', '\t']).trim_start_matches(',');
+        if rest.starts_with('*') || rest.starts_with("#+") {
+            let indent = line.len() - line.trim_start_matches([' ', '\t']).len();
+            out.push_str(&line[..indent]);
+            out.push(',');
+            out.push_str(&line[indent..]);
+        } else {
+            out.push_str(line);
+        }
+    }
+    out
+}
@@ -0,0 +1,59 @@
+use std::io::{self, Write};
+use unicode_width::UnicodeWidthStr;
+
+use crate::render::{FileEntry, RenderOptions};
+
+/// reStructuredText：`File:` 小节加 `.. code-block:: lang` 指令，可直接放进 Sphinx 文档树
+pub fn write(writer: &mut impl Write, entries: &[FileEntry], opts: &RenderOptions) -> io::Result<()> {
+    let text = opts.locale.strings();
+    let title = escape(opts.title);
+    let rule = "=".repeat(title.width().max(1));
+    writeln!(writer, "{}\n{}\n{}\n", rule, title, rule)?;
+    if opts.toc {
+        writeln!(writer, ".. contents::\n   :local:\n")?;
+    }
+    if let Some(preamble) = opts.preamble {
+        writeln!(writer, "{}\n", preamble)?;
+    }
+
+    for entry in entries {
+        let heading = format!("{}{}", text.file_prefix, escape(&entry.marked_path()));
+        writeln!(writer, "{}\n{}\n", heading, "-".repeat(heading.width()))?;
+        if let Some(original) = &entry.duplicate_of {
+            writeln!(writer, "{}\n", text.identical_to.replace("{path}", &format!("``{}``", original)))?;
+            continue;
+        }
+        if let Some(note) = &entry.placeholder {
+            writeln!(writer, "{}.\n", note)?;
+            continue;
+        }
+        let language = if entry.language.is_empty() { "text" } else { &entry.language };
+        writeln!(writer, ".. code-block:: {}", language)?;
+        if opts.line_numbers {
+            writeln!(writer, "   :linenos:")?;
+        }
+        writeln!(writer)?;
+        for line in entry.content.trim_end_matches('\n').split('\n') {
+            let line = line.trim_end_matches('\r');
+            if line.is_empty() {
+                writeln!(writer)?;
+            } else {
+                writeln!(writer, "   {}", line)?;
+            }
+        }
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+/// 标题中的 * ` _ | \ 会被当作行内标记，加反斜杠转义
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '*' | '`' | '_' | '|' | '\\') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
@@ -0,0 +1,103 @@
+use std::io::{self, Write};
+
+use crate::render::{FileEntry, RenderOptions};
+
+/// listings 认识的语言名称；不在其中的语言不设 language，只按等宽文本排版
+const LANGUAGES: &[(&str, &str)] = &[
+    ("c", "C"),
+    ("cpp", "C++"),
+    ("csharp", "[Sharp]C"),
+    ("java", "Java"),
+    ("python", "Python"),
+    ("ruby", "Ruby"),
+    ("bash", "bash"),
+    ("html", "HTML"),
+    ("xml", "XML"),
+    ("sql", "SQL"),
+    ("php", "PHP"),
+    ("perl", "Perl"),
+    ("haskell", "Haskell"),
+    ("lua", "Lua"),
+    ("r", "R"),
+    ("scala", "Scala"),
+    ("ocaml", "[Objective]Caml"),
+    ("erlang", "erlang"),
+    ("makefile", "make"),
+    ("latex", "[LaTeX]TeX"),
+];
+
+/// 独立的 LaTeX 文档：每个文件一节，内容放在 listings 环境中，可直接编译或并入论文附录
+pub fn write(writer: &mut impl Write, entries: &[FileEntry], opts: &RenderOptions) -> io::Result<()> {
+    let text = opts.locale.strings();
+    let environment = environment_name(entries);
+    writeln!(writer, "% Compile with xelatex or lualatex when the sources contain non-ASCII text.")?;
+    writeln!(writer, "\\documentclass{{article}}")?;
+    writeln!(writer, "\\usepackage{{iftex}}")?;
+    writeln!(writer, "\\ifPDFTeX\n  \\usepackage[utf8]{{inputenc}}\n  \\usepackage[T1]{{fontenc}}\n\\fi")?;
+    writeln!(writer, "\\usepackage[margin=2cm]{{geometry}}")?;
+    writeln!(writer, "\\usepackage{{xcolor}}")?;
+    writeln!(writer, "\\usepackage{{listings}}")?;
+    writeln!(writer, "\\usepackage{{hyperref}}")?;
+    writeln!(
+        writer,
+        "\\lstset{{basicstyle=\\ttfamily\\small, breaklines=true, columns=fullflexible, keepspaces=true, tabsize=4, showstringspaces=false, \
+         keywordstyle=\\color{{blue!70!black}}\\bfseries, commentstyle=\\color{{green!40!black}}\\itshape, stringstyle=\\color{{red!60!black}}{}}}",
+        if opts.line_numbers { ", numbers=left, numberstyle=\\tiny\\color{gray}" } else { "" }
+    )?;
+    writeln!(writer, "\\lstnewenvironment{{{}}}[1][]{{\\lstset{{#1}}}}{{}}", environment)?;
+    writeln!(writer, "\\title{{{}}}\n\\date{{}}", escape(opts.title))?;
+    writeln!(writer, "\n\\begin{{document}}\n\\maketitle")?;
+    if let Some(preamble) = opts.preamble {
+        writeln!(writer, "\n{}", escape(preamble))?;
+    }
+    if opts.toc {
+        writeln!(writer, "\\tableofcontents")?;
+    }
+
+    for entry in entries {
+        writeln!(writer, "\n\\section{{\\texttt{{{}}}}}", escape(&entry.marked_path()))?;
+        if let Some(original) = &entry.duplicate_of {
+            let (before, after) = text.identical_to.split_once("{path}").unwrap_or((text.identical_to, ""));
+            writeln!(writer, "{}\\texttt{{{}}}{}", escape(before), escape(original), escape(after))?;
+            continue;
+        }
+        if let Some(note) = &entry.placeholder {
+            writeln!(writer, "{}.", escape(note))?;
+            continue;
+        }
+        match LANGUAGES.iter().find(|(from, _)| *from == entry.language) {
+            Some((_, language)) => writeln!(writer, "\\begin{{{}}}[language={{{}}}]", environment, language)?,
+            None => writeln!(writer, "\\begin{{{}}}", environment)?,
+        }
+        writeln!(writer, "{}", entry.content.trim_end_matches('\n'))?;
+        writeln!(writer, "\\end{{{}}}", environment)?;
+    }
+    writeln!(writer, "\n\\end{{document}}")
+}
+
+/// 代码环境的名称；逐字环境遇到 \end{名称} 即结束，选一个所有文件内容中都没有出现的名称
+fn environment_name(entries: &[FileEntry]) -> String {
+    let mut name = String::from("sourcecode");
+    while entries.iter().any(|e| e.content.contains(&format!("\\end{{{}}}", name))) {
+        name.push('x');
+    }
+    name
+}
+
+/// 转义 LaTeX 的特殊字符，用于标题、路径与说明文字
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\textbackslash{}"),
+            '{' | '}' | '$' | '&' | '#' | '_' | '%' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '^' => out.push_str("\\textasciicircum{}"),
+            '~' => out.push_str("\\textasciitilde{}"),
+            c => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,28 @@
+use std::io::{self, Write};
+
+use crate::render::{self, FileEntry, RenderOptions};
+
+/// 纯文本拼接：`===== path =====` 分隔，内容原样输出，不做任何转义
+pub fn write(writer: &mut impl Write, entries: &[FileEntry], opts: &RenderOptions) -> io::Result<()> {
+    if let Some(preamble) = opts.preamble {
+        writeln!(writer, "{}\n", preamble)?;
+    }
+    for entry in entries {
+        writeln!(writer, "===== {} =====", entry.marked_path())?;
+        if let Some(original) = &entry.duplicate_of {
+            writeln!(writer, "(identical to {})\n", original)?;
+            continue;
+        }
+        if let Some(note) = &entry.placeholder {
+            writeln!(writer, "({})\n", note)?;
+            continue;
+        }
+        let content = render::display_content(entry, opts);
+        write!(writer, "{}", content)?;
+        if !content.ends_with('\n') {
+            writeln!(writer)?;
+        }
+        writeln!(writer)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use std::time::SystemTime;
+
+use crate::job::Job;
+use crate::render::format_iso;
+use crate::ConversionOptions;
+
+/// 文档开头的 YAML front matter：标题、生成时间、工具版本、文件数与本次使用的选项。
+/// 字符串一律写成 JSON 字符串，它同时也是合法的 YAML 双引号字符串
+pub fn render(title: &str, files: usize, job: &Job, options: &ConversionOptions) -> String {
+    let quote = |text: &str| serde_json::to_string(text).unwrap_or_default();
+    let list = |items: &[String]| format!("[{}]", items.iter().map(|i| quote(i)).collect::<Vec<_>>().join(", "));
+
+    let mut out = String::from("---\n");
+    out.push_str(&format!("title: {}\n", quote(title)));
+    out.push_str(&format!("generator: {}\n", quote(&format!("code2md {}", env!("CARGO_PKG_VERSION")))));
+    out.push_str(&format!("generated: {}\n", format_iso(SystemTime::now())));
+    out.push_str(&format!("files: {}\n", files));
+
+    out.push_str("options:\n");
+    out.push_str(&format!("  format: {:?}\n", job.format).to_lowercase());
+    out.push_str(&format!("  sort: {:?}\n", job.sort).to_lowercase());
+    let flags = [
+        ("tree", options.tree),
+        ("toc", options.toc),
+        ("group_by_dir", options.group_by_dir),
+        ("line_numbers", options.line_numbers),
+        ("metadata", options.metadata),
+        ("redact", job.redact),
+        ("strip_comments", options.strip_comments),
+        ("compact", options.compact),
+        ("outline", options.outline),
+        ("normalize_eol", options.normalize_eol),
+        ("dedup", options.dedup),
+        ("git_only", job.git_only),
+        ("follow_symlinks", job.follow_symlinks),
+    ];
+    for (name, value) in flags {
+        out.push_str(&format!("  {}: {}\n", name, value));
+    }
+    if !options.include.is_empty() {
+        out.push_str(&format!("  include: {}\n", list(&options.include)));
+    }
+    if !options.exclude.is_empty() {
+        out.push_str(&format!("  exclude: {}\n", list(&options.exclude)));
+    }
+    if let Some(rev) = &job.since {
+        out.push_str(&format!("  since: {}\n", quote(rev)));
+    }
+    if let Some(depth) = job.max_depth {
+        out.push_str(&format!("  max_depth: {}\n", depth));
+    }
+    if let Some(size) = options.max_file_size {
+        out.push_str(&format!("  max_file_size: {}\n", size));
+    }
+    out.push_str("---\n\n");
+    out
+}
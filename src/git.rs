@@ -0,0 +1,72 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// 在 dir 下执行 git，返回以 NUL 分隔的输出所对应的路径（相对于 dir）
+fn run_z(dir: &Path, args: &[&str]) -> io::Result<Vec<PathBuf>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| io::Error::new(e.kind(), format!("无法执行 git：{}", e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(io::Error::other(format!("git {} 失败：{}", args.join(" "), stderr.trim())));
+    }
+
+    Ok(output
+        .stdout
+        .split(|b| *b == 0)
+        .filter(|s| !s.is_empty())
+        .map(|s| PathBuf::from(String::from_utf8_lossy(s).into_owned()))
+        .collect())
+}
+
+/// dir 下所有被 git 跟踪的文件（绝对路径）
+pub fn tracked_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let files = run_z(dir, &["ls-files", "-z", "--cached", "--", "."])?;
+    Ok(files.into_iter().map(|f| dir.join(f)).collect())
+}
+
+/// 相对于 rev 有改动的文件（含工作区未提交的修改与未跟踪的新文件，不含已删除的）
+pub fn changed_since(dir: &Path, rev: &str) -> io::Result<Vec<PathBuf>> {
+    let mut files = run_z(dir, &["diff", "--name-only", "-z", "--relative", "--diff-filter=d", rev, "--", "."])?;
+    files.extend(run_z(dir, &["ls-files", "-z", "--others", "--exclude-standard", "--", "."])?);
+    files.sort();
+    files.dedup();
+    Ok(files.into_iter().map(|f| dir.join(f)).collect())
+}
+
+/// 形如 https://…、git@host:…、ssh://…、file://… 的仓库地址
+pub fn is_remote_url(text: &str) -> bool {
+    ["https://", "http://", "ssh://", "git://", "file://", "git@"].iter().any(|p| text.starts_with(p))
+}
+
+/// 远程地址对应的仓库名：`https://github.com/user/repo.git` -> `repo`
+pub fn repo_name(url: &str) -> &str {
+    let last = url.trim_end_matches('/').rsplit(['/', ':']).next().unwrap_or(url);
+    last.strip_suffix(".git").unwrap_or(last)
+}
+
+/// 浅克隆（--depth 1）到 dest，branch 可为分支或标签
+pub fn shallow_clone(url: &str, branch: Option<&str>, dest: &Path) -> io::Result<()> {
+    let mut command = Command::new("git");
+    command.args(["clone", "--depth", "1", "--quiet"]);
+    if let Some(branch) = branch {
+        command.args(["--branch", branch]);
+    }
+    let output = command
+        .arg("--")
+        .arg(url)
+        .arg(dest)
+        .output()
+        .map_err(|e| io::Error::new(e.kind(), format!("无法执行 git：{}", e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(io::Error::other(format!("克隆 {} 失败：{}", url, stderr.trim())));
+    }
+    Ok(())
+}
@@ -0,0 +1,171 @@
+//! 双击运行时的拖放窗口：把文件夹或压缩包拖进来即按默认设置生成文档，
+//! 窗口中显示进度与输出位置，供不使用命令行的同事使用。
+
+use std::io;
+
+#[cfg(not(windows))]
+pub fn run() -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "拖放窗口仅支持 Windows"))
+}
+
+#[cfg(windows)]
+pub fn run() -> io::Result<()> {
+    imp::run()
+}
+
+#[cfg(windows)]
+mod imp {
+    use std::ffi::{c_void, OsString};
+    use std::io;
+    use std::os::windows::ffi::{OsStrExt, OsStringExt};
+    use std::path::PathBuf;
+    use std::ptr::null_mut;
+    use std::sync::atomic::{AtomicBool, AtomicPtr, Ordering};
+
+    use windows_sys::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
+    use windows_sys::Win32::Graphics::Gdi::{GetStockObject, COLOR_WINDOW, DEFAULT_GUI_FONT};
+    use windows_sys::Win32::System::LibraryLoader::GetModuleHandleW;
+    use windows_sys::Win32::UI::Shell::{DragAcceptFiles, DragFinish, DragQueryFileW, HDROP};
+    use windows_sys::Win32::UI::WindowsAndMessaging::*;
+
+    /// STATIC 控件文字居中（定义在 SystemServices 中，不为一个常量引入整个模块）
+    const SS_CENTER: u32 = 0x1;
+
+    /// 工作线程把状态文字装箱后经此消息交给界面线程
+    const WM_STATUS: u32 = WM_APP + 1;
+
+    static LABEL: AtomicPtr<c_void> = AtomicPtr::new(null_mut());
+    static BUSY: AtomicBool = AtomicBool::new(false);
+
+    fn wide(text: &str) -> Vec<u16> {
+        std::ffi::OsStr::new(text).encode_wide().chain(Some(0)).collect()
+    }
+
+    pub fn run() -> io::Result<()> {
+        let class = wide("code2md.drop");
+        let title = wide("code2md");
+        let hint = wide("将项目文件夹或压缩包拖放到此窗口\r\n\r\n文档会保存在它旁边");
+
+        unsafe {
+            let instance = GetModuleHandleW(null_mut());
+            let wc = WNDCLASSW {
+                lpfnWndProc: Some(wnd_proc),
+                hInstance: instance,
+                hCursor: LoadCursorW(null_mut(), IDC_ARROW),
+                hbrBackground: (COLOR_WINDOW + 1) as usize as _,
+                lpszClassName: class.as_ptr(),
+                ..std::mem::zeroed()
+            };
+            if RegisterClassW(&wc) == 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            let hwnd = CreateWindowExW(
+                WS_EX_ACCEPTFILES,
+                class.as_ptr(),
+                title.as_ptr(),
+                WS_OVERLAPPEDWINDOW & !WS_MAXIMIZEBOX & !WS_THICKFRAME | WS_VISIBLE,
+                CW_USEDEFAULT,
+                CW_USEDEFAULT,
+                480,
+                220,
+                null_mut(),
+                null_mut(),
+                instance,
+                null_mut(),
+            );
+            if hwnd.is_null() {
+                return Err(io::Error::last_os_error());
+            }
+
+            let label = CreateWindowExW(
+                0,
+                wide("STATIC").as_ptr(),
+                hint.as_ptr(),
+                WS_CHILD | WS_VISIBLE | SS_CENTER,
+                16,
+                40,
+                432,
+                120,
+                hwnd,
+                null_mut(),
+                instance,
+                null_mut(),
+            );
+            SendMessageW(label, WM_SETFONT, GetStockObject(DEFAULT_GUI_FONT) as usize, 1);
+            LABEL.store(label, Ordering::Relaxed);
+            DragAcceptFiles(hwnd, 1);
+
+            let mut msg: MSG = std::mem::zeroed();
+            while GetMessageW(&mut msg, null_mut(), 0, 0) > 0 {
+                TranslateMessage(&msg);
+                DispatchMessageW(&msg);
+            }
+        }
+        Ok(())
+    }
+
+    unsafe extern "system" fn wnd_proc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        match msg {
+            WM_DROPFILES => {
+                let paths = dropped_paths(wparam as HDROP);
+                if !BUSY.swap(true, Ordering::SeqCst) {
+                    convert_in_background(hwnd, paths);
+                }
+                0
+            }
+            WM_STATUS => {
+                let text = Box::from_raw(lparam as *mut String);
+                SetWindowTextW(LABEL.load(Ordering::Relaxed), wide(&text).as_ptr());
+                0
+            }
+            WM_DESTROY => {
+                PostQuitMessage(0);
+                0
+            }
+            _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+        }
+    }
+
+    unsafe fn dropped_paths(drop: HDROP) -> Vec<PathBuf> {
+        let count = DragQueryFileW(drop, u32::MAX, null_mut(), 0);
+        let mut paths = Vec::with_capacity(count as usize);
+        for i in 0..count {
+            let len = DragQueryFileW(drop, i, null_mut(), 0);
+            let mut buf = vec![0u16; len as usize + 1];
+            DragQueryFileW(drop, i, buf.as_mut_ptr(), buf.len() as u32);
+            buf.truncate(len as usize);
+            paths.push(PathBuf::from(OsString::from_wide(&buf)));
+        }
+        DragFinish(drop);
+        paths
+    }
+
+    /// 在工作线程中逐个转换，避免界面卡住；窗口句柄以整数形式传过去
+    fn convert_in_background(hwnd: HWND, paths: Vec<PathBuf>) {
+        let hwnd = hwnd as usize;
+        std::thread::spawn(move || {
+            let status = |text: String| unsafe {
+                PostMessageW(hwnd as HWND, WM_STATUS, 0, Box::into_raw(Box::new(text)) as LPARAM);
+            };
+
+            let mut lines = Vec::new();
+            for (i, path) in paths.iter().enumerate() {
+                let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+                status(format!("正在转换 {}（{}/{}）…", name, i + 1, paths.len()));
+                match code2xml::convert(path, &code2xml::ConversionOptions::default()) {
+                    Ok(report) => {
+                        for output in &report.outputs {
+                            lines.push(format!("已生成 {}（{} 个文件）", output.display(), report.entries.len()));
+                        }
+                    }
+                    Err(e) => lines.push(format!("{} 转换失败：{}", name, e)),
+                }
+            }
+            lines.push(String::new());
+            lines.push("可以继续拖放其他文件夹".to_string());
+            status(lines.join("\r\n"));
+            BUSY.store(false, Ordering::SeqCst);
+        });
+    }
+}
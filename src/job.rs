@@ -0,0 +1,950 @@
+use ignore::WalkBuilder;
+use indicatif::HumanBytes;
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+use tracing::{debug, info, warn};
+
+use crate::archive;
+use crate::cache::{Cache, CachedFile, CACHE_FILE_NAME};
+use crate::compact;
+use crate::config::{self, Config};
+use crate::filter::{self, Filter, IGNORE_FILE_NAME};
+use crate::encoding;
+use crate::format::Format;
+use crate::git;
+use crate::lang;
+use crate::outline;
+use crate::manifest;
+use crate::pathname;
+use crate::pdf;
+use crate::progress::Progress;
+use crate::redact;
+use crate::locale::Locale;
+use crate::render::FileEntry;
+use crate::sort::SortOrder;
+use crate::split;
+use crate::{ConversionOptions, Error};
+use crate::stats::{Skip, SkipCounts};
+use crate::stream::{Scanned, Stream, STREAM_THRESHOLD};
+
+/// 遍历时记录下的文件及其根目录下标，可在遍历器的回调中共享
+type Recorded = Arc<Mutex<Vec<(usize, PathBuf)>>>;
+
+/// 一个扫描根目录
+#[derive(Clone)]
+pub struct Root {
+    pub path: PathBuf,
+    /// 多个根目录时用作分组标题与路径前缀；只有一个根目录时为空
+    pub label: String,
+    /// --relative-to 与 --path-prefix 加在显示路径前的部分，非空时以 / 结尾
+    pub prefix: String,
+    pub filter: Arc<Filter>,
+}
+
+/// 一次运行所需的全部上下文：源目录、输出路径与过滤规则
+pub struct Job {
+    pub roots: Vec<Root>,
+    /// 所有根目录的公共祖先（单个根目录时即其本身），决定配置、默认输出位置与项目名
+    pub base: PathBuf,
+    /// None 表示写到标准输出；split_files 时为输出目录
+    pub output_path: Option<PathBuf>,
+    /// 每个文件单独写成一页
+    pub split_files: bool,
+    pub format: Format,
+    pub sort: SortOrder,
+    /// 文件标题的级别与模板
+    pub heading_level: usize,
+    pub heading_template: String,
+    /// 标题等固定文字的语言
+    pub locale: Locale,
+    /// 文档标题，默认为项目名
+    pub title: Option<String>,
+    /// 只收录 git 跟踪的文件，取代目录遍历
+    pub git_only: bool,
+    /// 只收录相对于该 git 提交有改动的文件
+    pub since: Option<String>,
+    /// 明确给出的文件列表（已规范化），取代目录遍历与名称层面的规则
+    pub files: Option<Vec<PathBuf>>,
+    /// 跟随符号链接，并按真实路径去重
+    pub follow_symlinks: bool,
+    /// 配置文件中的扩展名到语言标识映射（键已统一为不带点的小写）
+    pub languages: HashMap<String, String>,
+    /// 写出前遮盖疑似密钥
+    pub redact: bool,
+    /// 已遮盖的疑似密钥处数
+    pub redacted: AtomicUsize,
+    /// 收录 PDF，内容为从中提取的文字
+    pub extract_pdf: bool,
+    /// 去掉注释
+    pub strip_comments: bool,
+    /// 合并连续空行、去掉行尾空白
+    pub compact: bool,
+    /// 只保留定义所在的行
+    pub outline: bool,
+    /// 把 CRLF 换行统一为 LF
+    pub normalize_eol: bool,
+    /// 超过该行数的文件只保留开头与结尾各一半
+    pub max_lines: Option<usize>,
+    /// 以显示路径为键，只收录这些文件中指定的行
+    pub line_ranges: HashMap<String, Vec<RangeInclusive<usize>>>,
+    /// 不小于该大小的文件在写文档时才流式读取；None 为全部读入内存（需要完整内容的功能开启时）
+    pub stream_threshold: Option<u64>,
+    /// 相对于各根目录的最大深度，None 为不限
+    pub max_depth: Option<usize>,
+    pub progress: Progress,
+    /// 各原因跳过的文件数，供摘要使用
+    pub skipped: Arc<SkipCounts>,
+    /// 交互模式下手动选择的文件，Some 时取代内容层面的过滤规则
+    pub selection: Option<HashSet<String>>,
+    /// 二进制与过大的文件仍以标题加一行说明的形式出现在文档中
+    pub skipped_inline: bool,
+    /// 缓存文件的位置；None 为不使用缓存（--no-cache、只写到标准输出、压缩包与远程仓库）
+    cache_path: Option<PathBuf>,
+    /// 上次运行留下的缓存
+    cache: Cache,
+    /// 本次处理过的文件，写回缓存时取代旧内容
+    fresh_cache: Mutex<Cache>,
+    /// 压缩包解压出的、远程仓库克隆到的临时目录，随 Job 一起删除
+    _extracted: Vec<TempDir>,
+}
+
+impl Job {
+    pub fn new(paths: &[PathBuf], options: &ConversionOptions, progress: Progress) -> crate::Result<Job> {
+        let inputs: Vec<&Path> = if paths.is_empty() {
+            vec![Path::new(".")]
+        } else {
+            paths.iter().map(PathBuf::as_path).collect()
+        };
+        // 不存在且含通配符的输入视为模式，从模式之前的目录开始遍历
+        let globs: Vec<Option<(PathBuf, String)>> = inputs.iter().map(|input| split_glob(input)).collect();
+        let inputs: Vec<&Path> = inputs.iter().zip(&globs)
+            .map(|(input, glob)| glob.as_ref().map_or(*input, |(base, _)| base.as_path()))
+            .collect();
+        // sources 为实际扫描的目录；locations 为它们在用户眼中的位置。
+        // 压缩包解压在临时目录中，其位置取压缩包去掉扩展名后的路径；远程仓库克隆到临时目录
+        let mut sources = Vec::with_capacity(inputs.len());
+        let mut locations = Vec::with_capacity(inputs.len());
+        let mut extracted = Vec::new();
+        for input in &inputs {
+            if let Some(url) = input.to_str().filter(|s| git::is_remote_url(s)) {
+                let dir = tempfile::Builder::new().prefix("code2md-").tempdir()?;
+                let name = git::repo_name(url);
+                let checkout = dir.path().join(name);
+                info!("克隆 {} 到 {}", url, checkout.display());
+                git::shallow_clone(url, options.branch.as_deref(), &checkout)?;
+                sources.push(checkout.canonicalize()?);
+                // 远程仓库视为位于当前目录下，默认输出为 ./repo.md
+                locations.push(std::env::current_dir()?.join(name));
+                extracted.push(dir);
+                continue;
+            }
+            let canonical = input.canonicalize().map_err(|e| match e.kind() {
+                io::ErrorKind::NotFound => Error::PathNotFound(input.to_path_buf()),
+                _ => Error::Io(e),
+            })?;
+            if archive::is_archive(&canonical) {
+                info!("解压 {}", canonical.display());
+                let archive = archive::extract(&canonical)?;
+                // 规范化后 Windows 上为扩展长度路径，解压出的深层目录也能遍历
+                sources.push(archive.root.canonicalize()?);
+                locations.push(archive::stem_path(&canonical));
+                extracted.push(archive.dir);
+            } else {
+                sources.push(canonical.clone());
+                locations.push(canonical);
+            }
+        }
+        let base = common_ancestor(&locations);
+
+        let config_dir = if sources.len() == 1 { &sources[0] } else { &base };
+        let mut config = Config::load(config_dir)?;
+        config.exclude.extend(options.exclude.iter().cloned());
+        config.include.extend(options.include.iter().cloned());
+        if options.skip_secrets {
+            config.skip_secrets = Some(true);
+        }
+        if options.include_md {
+            config.include_md = Some(true);
+        }
+        if options.extract_pdf {
+            config.extract_pdf = Some(true);
+        }
+        if options.hidden {
+            config.hidden = Some(true);
+        }
+        if options.max_file_size.is_some() {
+            config.max_file_size = options.max_file_size;
+        }
+        let format = options.format.or(config.format).unwrap_or_default();
+        let sort = options.sort.or(config.sort).unwrap_or_default();
+        let heading_level = options.heading_level.or(config.heading_level).unwrap_or(2);
+        if !(1..=6).contains(&heading_level) {
+            return Err(Error::InvalidOptions(format!("标题级别须在 1 到 6 之间：{}", heading_level)));
+        }
+        let locale = options.locale.unwrap_or_else(Locale::detect);
+        let heading_template = options.heading.clone().or(config.heading.clone())
+            .unwrap_or_else(|| locale.strings().heading_template.to_string());
+        if !heading_template.contains("{path}") {
+            return Err(Error::InvalidOptions(format!("标题模板中缺少 {{path}}：{}", heading_template)));
+        }
+        let git_only = options.git_only || config.git_only.unwrap_or(false);
+        let redact = options.redact && config.redact.unwrap_or(true);
+        let languages: HashMap<String, String> = config.languages.iter()
+            .map(|(ext, language)| (ext.trim_start_matches('.').to_lowercase(), language.clone()))
+            .collect();
+        let follow_symlinks = options.follow_symlinks || config.follow_symlinks.unwrap_or(false);
+
+        if let Some(name) = &options.name {
+            if name.is_empty() || name.contains(['/', '\\']) || name == "." || name == ".." {
+                return Err(Error::InvalidOptions(format!("--name 应为不含目录的文件名：{}", name)));
+            }
+        }
+
+        // 优先级：命令行 > 配置文件 > 默认位置；--name 只改默认位置下的文件名
+        let save_inside = options.save_inside || config.inside.unwrap_or(false);
+        let output_path = match &options.output {
+            _ if options.stdout || options.no_file => None,
+            Some(out) if out.as_os_str() == "-" => None,
+            Some(out) => Some(normalize_output(out)?),
+            None => Some(match &config.output {
+                Some(out) if options.name.is_none() => normalize_output(&base.join(out))?,
+                _ if options.split_files => {
+                    pages_dir_for(&output_path_for(&base, options.name.as_deref(), save_inside, format))
+                }
+                _ => output_path_for(&base, options.name.as_deref(), save_inside, format),
+            }),
+        };
+
+        // 临时目录中的文件每次都是新解压、新克隆的，缓存没有意义；
+        // 只写到标准输出（--stdout、--no-file）时也不在项目中留下缓存
+        let cache_path = (options.cache && extracted.is_empty() && output_path.is_some()).then(|| config_dir.join(CACHE_FILE_NAME));
+        // 命令行给出的范围覆盖配置文件中同一文件的设置
+        let mut line_ranges = HashMap::new();
+        let specs = config.lines.iter().chain(options.lines.iter().map(|(path, ranges)| (path, ranges)));
+        for (path, ranges) in specs {
+            let key = path.replace('\\', "/").trim_start_matches("./").to_string();
+            line_ranges.insert(key, config::parse_line_ranges(ranges).map_err(Error::InvalidOptions)?);
+        }
+        let settings = cache_settings(redact, options, &config, &languages, &line_ranges);
+        let cache = match &cache_path {
+            Some(path) => Cache::load(path, &settings),
+            None => Cache::default(),
+        };
+
+        let relative_to = match &options.relative_to {
+            Some(base) => Some(base.canonicalize().map_err(|e| match e.kind() {
+                io::ErrorKind::NotFound => Error::PathNotFound(base.clone()),
+                _ => Error::Io(e),
+            })?),
+            None => None,
+        };
+        let path_prefix = options.path_prefix.as_deref().map(|p| p.replace('\\', "/")).unwrap_or_default();
+        let path_prefix = path_prefix.trim_matches('/');
+
+        let multiple = sources.len() > 1;
+        let mut roots = Vec::with_capacity(sources.len());
+        for (((input, path), location), glob) in inputs.iter().zip(sources).zip(&locations).zip(&globs) {
+            let label = if !multiple {
+                String::new()
+            } else if path != *location {
+                project_name(location)
+            } else {
+                root_label(input, &path)
+            };
+            let mut filter = Filter::new(&path, &config)?;
+            if let Some((_, pattern)) = glob {
+                filter.restrict_to(pattern)?;
+            }
+            let filter = Arc::new(filter);
+            let mut prefix = if path_prefix.is_empty() { String::new() } else { format!("{}/", path_prefix) };
+            if let Some(base) = &relative_to {
+                let rel = location.strip_prefix(base).map_err(|_| {
+                    Error::InvalidOptions(format!("{} 不在 --relative-to 给出的 {} 之下", location.display(), base.display()))
+                })?;
+                if !rel.as_os_str().is_empty() {
+                    prefix.push_str(&pathname::display(rel));
+                    prefix.push('/');
+                }
+            }
+            roots.push(Root { path, label, prefix, filter });
+        }
+        let files = options.files.as_ref().map(|files| canonical_files(files));
+        for path in files.iter().flatten().filter(|p| !roots.iter().any(|r| p.starts_with(&r.path))) {
+            warn!("跳过 {}：不在任何项目目录中", path.display());
+        }
+
+        Ok(Job {
+            roots,
+            base,
+            output_path,
+            split_files: options.split_files,
+            format,
+            sort,
+            heading_level,
+            heading_template,
+            locale,
+            title: options.title.clone(),
+            git_only,
+            since: options.since.clone(),
+            files,
+            follow_symlinks,
+            languages,
+            redact,
+            redacted: AtomicUsize::new(0),
+            extract_pdf: config.extract_pdf.unwrap_or(false),
+            strip_comments: options.strip_comments,
+            compact: options.compact,
+            outline: options.outline,
+            normalize_eol: options.normalize_eol,
+            max_lines: options.max_lines_per_file,
+            line_ranges,
+            stream_threshold: streamable(options, format).then_some(STREAM_THRESHOLD),
+            max_depth: options.max_depth,
+            progress,
+            skipped: Arc::default(),
+            selection: options.selection.as_ref().map(|files| files.iter().cloned().collect()),
+            skipped_inline: options.skipped_inline,
+            cache_path,
+            cache,
+            fresh_cache: Mutex::new(Cache::new(settings)),
+            _extracted: extracted,
+        })
+    }
+
+    /// 输出文件本身及其分卷、清单、缓存文件不应被再次收录
+    pub fn is_own_output(&self, path: &Path) -> bool {
+        // 即使本次 --no-cache，上次留下的缓存也不收录
+        if path.file_name().is_some_and(|n| n == CACHE_FILE_NAME) {
+            return true;
+        }
+        let (Some(output), Some(name)) = (self.output_path.as_deref(), path.file_name()) else {
+            return false;
+        };
+        if self.split_files {
+            return path.starts_with(output);
+        }
+        if Some(name) == output.file_name() || Some(name) == manifest::manifest_path(output).file_name() {
+            return true;
+        }
+        split::is_part_of(output, &name.to_string_lossy())
+    }
+
+    pub fn redacted(&self) -> usize {
+        self.redacted.load(Ordering::Relaxed)
+    }
+
+    /// 文件的显示路径（多根目录时带根目录前缀），不含 --relative-to 与 --path-prefix 的前缀
+    pub fn display_path(&self, index: usize, path: &Path) -> String {
+        display_path(&self.roots[index], path)
+    }
+
+    /// 文件在文档中实际显示的路径
+    pub fn shown_path(&self, index: usize, path: &Path) -> String {
+        shown_path(&self.roots[index], path)
+    }
+
+    pub fn project_name(&self) -> String {
+        project_name(&self.base)
+    }
+
+    /// 文档标题：--title 给出的标题，否则为项目名
+    pub fn title(&self) -> String {
+        self.title.clone().unwrap_or_else(|| self.project_name())
+    }
+
+    /// 遍历所有根目录，返回应当输出的文件及其所属根目录的下标（已排除输出文件自身）
+    pub fn collect_files(&self) -> io::Result<Vec<(usize, PathBuf)>> {
+        Ok(self.checked_files()?
+            .into_iter()
+            .filter(|(_, _, reason)| reason.is_none())
+            .map(|(index, path, _)| (index, path))
+            .collect())
+    }
+
+    /// 同 collect_files，但保留被内容层面的检查拒绝的文件及原因（None 为应当输出）
+    fn checked_files(&self) -> io::Result<Vec<(usize, PathBuf, Option<Skip>)>> {
+        let mut candidates = self.walk()?;
+        // 交互选择过的文件不再经过内容层面的检查
+        if let Some(selection) = &self.selection {
+            candidates.retain(|(index, path)| selection.contains(&self.display_path(*index, path)));
+            self.progress.start_reading(candidates.len());
+            return Ok(candidates.into_iter().map(|(index, path)| (index, path, None)).collect());
+        }
+        self.progress.start_reading(candidates.len());
+
+        // 有序的 par_iter 保证输出顺序与遍历顺序一致
+        let out_file_abs = self.output_abs();
+        Ok(candidates
+            .into_par_iter()
+            .map(|(index, path)| {
+                let reason = self.check(index, &path, out_file_abs.as_deref()).err();
+                if reason.is_some() { self.progress.processed(); }
+                (index, path, reason)
+            })
+            .collect())
+    }
+
+    /// 遍历到的全部文件及其未被收录的原因（None 为会被收录），供交互选择与 --dry-run 使用。
+    /// 被忽略规则剪掉的目录作为一项列出（路径为目录本身），其中的文件不再展开；
+    /// .gitignore 的规则由遍历器内部处理，不在其中
+    pub fn scan(&self) -> io::Result<Vec<(usize, PathBuf, Option<Skip>)>> {
+        let ignored = Arc::new(Mutex::new(Vec::new()));
+        let candidates = self.walk_recording(Some(Arc::clone(&ignored)))?;
+        let out_file_abs = self.output_abs();
+        let mut files: Vec<(usize, PathBuf, Option<Skip>)> = candidates
+            .into_par_iter()
+            .map(|(index, path)| {
+                let reason = self.check(index, &path, out_file_abs.as_deref()).err();
+                (index, path, reason)
+            })
+            .collect();
+        let ignored = std::mem::take(&mut *ignored.lock().unwrap());
+        files.extend(ignored.into_iter().map(|(index, path)| (index, path, Some(Skip::Ignored))));
+        Ok(files)
+    }
+
+    fn output_abs(&self) -> Option<PathBuf> {
+        self.output_path.as_deref().map(|p| p.canonicalize().unwrap_or_else(|_| p.to_path_buf()))
+    }
+
+    /// 只做名称层面的过滤；需要访问文件的检查由 accepts 并行进行
+    fn walk(&self) -> io::Result<Vec<(usize, PathBuf)>> {
+        self.walk_recording(None)
+    }
+
+    /// 同 walk，并把被名称规则忽略的文件与目录记入 ignored
+    fn walk_recording(&self, ignored: Option<Recorded>) -> io::Result<Vec<(usize, PathBuf)>> {
+        let mut candidates: Vec<(usize, PathBuf)> = Vec::new();
+        for (index, root) in self.roots.iter().enumerate() {
+            if let Some(listed) = self.listed_files(root)? {
+                for path in listed {
+                    if !path.is_file() || self.is_own_output(&path) || !self.within_depth(root, &path) { continue; }
+                    if self.files.is_none() && root.filter.rejects_listed(&path) {
+                        self.skipped.add(Skip::Ignored, shown_path(root, &path));
+                        log_skip(&path, Skip::Ignored);
+                        if let Some(ignored) = &ignored { ignored.lock().unwrap().push((index, path)); }
+                        continue;
+                    }
+                    self.progress.scanned();
+                    candidates.push((index, path));
+                }
+                continue;
+            }
+
+            // 遵循 .gitignore（包括子目录中的），即使不在 git 仓库内
+            let walked = root.clone();
+            let skipped = Arc::clone(&self.skipped);
+            let ignored = ignored.clone();
+            let walker = WalkBuilder::new(&root.path)
+                .standard_filters(false)
+                .git_ignore(true)
+                .git_global(true)
+                .git_exclude(true)
+                .require_git(false)
+                .add_custom_ignore_filename(IGNORE_FILE_NAME)
+                .parents(true)
+                .max_depth(self.max_depth)
+                // 链接成环时 ignore 会返回 Loop 错误，随其他遍历错误一起被丢弃
+                .follow_links(self.follow_symlinks)
+                .filter_entry(move |e| {
+                    let rejected = walked.filter.is_hidden_or_ignored(e);
+                    if rejected && e.file_type().is_some_and(|t| t.is_dir()) {
+                        debug!("跳过目录 {}：{}", e.path().display(), Skip::Ignored.describe());
+                        skipped.add_dir(shown_path(&walked, e.path()));
+                    } else if rejected {
+                        skipped.add(Skip::Ignored, shown_path(&walked, e.path()));
+                        log_skip(e.path(), Skip::Ignored);
+                    }
+                    if let (true, Some(ignored)) = (rejected, &ignored) {
+                        ignored.lock().unwrap().push((index, e.path().to_path_buf()));
+                    }
+                    !rejected
+                })
+                .build();
+
+            candidates.extend(
+                walker
+                    .filter_map(|entry| entry.map_err(|e| warn!("遍历出错：{}", e)).ok())
+                    .filter(|e| !e.file_type().is_some_and(|t| t.is_dir()))
+                    .map(ignore::DirEntry::into_path)
+                    .filter(|p| !self.is_own_output(p))
+                    .inspect(|_| self.progress.scanned())
+                    .map(|p| (index, p)),
+            );
+        }
+        if self.follow_symlinks {
+            dedup_by_target(&mut candidates);
+        }
+        Ok(candidates)
+    }
+
+    /// 内容层面的检查，并按原因记录跳过的文件
+    fn check(&self, index: usize, path: &Path, out_file_abs: Option<&Path>) -> Result<(), Skip> {
+        if let (Some(out_abs), Ok(abs)) = (out_file_abs, path.canonicalize()) {
+            if abs == out_abs { return Err(Skip::Ignored); }
+        }
+        let root = &self.roots[index];
+        let result = root.filter.check_file(path);
+        if let Err(reason) = result {
+            log_skip(path, reason);
+            self.skipped.add(reason, shown_path(root, path));
+        }
+        result
+    }
+
+    /// 与目录遍历的 max_depth 语义一致：根目录下的直接文件深度为 1
+    pub(crate) fn within_depth(&self, root: &Root, path: &Path) -> bool {
+        let Some(max) = self.max_depth else { return true };
+        path.strip_prefix(&root.path).map_or(true, |rel| rel.components().count() <= max)
+    }
+
+    /// git 模式下由 git 直接给出候选文件，否则返回 None 表示需要遍历目录
+    pub(crate) fn listed_files(&self, root: &Root) -> io::Result<Option<Vec<PathBuf>>> {
+        if let Some(files) = &self.files {
+            return Ok(Some(files.iter().filter(|p| p.starts_with(&root.path)).cloned().collect()));
+        }
+        if let Some(rev) = &self.since {
+            return git::changed_since(&root.path, rev).map(Some);
+        }
+        if self.git_only {
+            return git::tracked_files(&root.path).map(Some);
+        }
+        Ok(None)
+    }
+
+    /// 读取所有候选文件，跳过无法读取或内容为空的文件；
+    /// skipped_inline 时二进制与过大的文件以占位项保留
+    pub fn load_entries(&self) -> io::Result<Vec<FileEntry>> {
+        Ok(self.checked_files()?
+            .into_par_iter()
+            .filter_map(|(index, path, reason)| match reason {
+                None => {
+                    let entry = self.load_entry(&self.roots[index], &path);
+                    self.progress.processed();
+                    if let Some(e) = &entry {
+                        self.progress.included(if e.stream.is_some() { e.size } else { e.content.len() as u64 });
+                    }
+                    entry
+                }
+                Some(reason @ (Skip::Binary | Skip::TooLarge)) if self.skipped_inline => {
+                    Some(placeholder_entry(&self.roots[index], &path, reason))
+                }
+                Some(_) => None,
+            })
+            .collect())
+    }
+
+    /// 把本次处理过的文件写回缓存；失败不影响文档本身
+    pub fn save_cache(&self) {
+        let Some(path) = &self.cache_path else { return };
+        if let Err(e) = self.fresh_cache.lock().unwrap().save(path) {
+            warn!("无法写入缓存 {}：{}", path.display(), e);
+        }
+    }
+
+    fn load_entry(&self, root: &Root, path: &Path) -> Option<FileEntry> {
+        let key = display_path(root, path);
+        // --lines 与配置中的 [lines] 按文档标题中的路径（含 --path-prefix 的前缀）指定
+        let shown = shown_path(root, path);
+        let metadata = fs::metadata(pathname::extended(path)).ok();
+        let modified = metadata.as_ref().and_then(|m| m.modified().ok());
+        let stamp = metadata.as_ref().map(|m| m.len()).zip(modified);
+        let pdf = self.extract_pdf && filter::is_pdf(path);
+        let large = !pdf && self.stream_threshold.zip(stamp).is_some_and(|(threshold, (size, _))| size >= threshold);
+        if large && !self.line_ranges.contains_key(&shown) {
+            match self.stream_entry(root, path) {
+                Some(entry) => return entry,
+                // 含私钥块，读入内存以便整段遮盖
+                None => debug!("{} 含私钥，不流式读取", path.display()),
+            }
+        }
+        if let Some(cached) = stamp.and_then(|(size, modified)| self.cache.get(&key, size, modified)) {
+            debug!("沿用缓存 {}", path.display());
+            self.redacted.fetch_add(cached.redacted, Ordering::Relaxed);
+            self.fresh_cache.lock().unwrap().insert(key, cached.clone());
+            return Some(self.entry(root, path, cached.clone()));
+        }
+
+        let bytes = match fs::read(pathname::extended(path)) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                warn!("无法读取 {}：{}", path.display(), e);
+                self.skipped.add(Skip::Unreadable, shown_path(root, path));
+                return None;
+            }
+        };
+        let size = bytes.len() as u64;
+        let mut content = if pdf {
+            match pdf::extract_text(&bytes) {
+                Ok(text) => text,
+                Err(reason) => {
+                    warn!("无法提取 {} 中的文字：{}", path.display(), reason);
+                    self.skipped.add(Skip::Unreadable, shown_path(root, path));
+                    return None;
+                }
+            }
+        } else {
+            encoding::decode(&bytes)
+        };
+        if self.normalize_eol {
+            let (normalized, mixed) = encoding::normalize_eol(&content);
+            if mixed {
+                warn!("{} 混用了 CRLF 与 LF 换行", path.display());
+            }
+            content = normalized;
+        }
+        let ranges = self.line_ranges.get(&shown);
+        if let Some(ranges) = ranges {
+            content = compact::select_lines(&content, ranges);
+        }
+        if content.trim().is_empty() {
+            log_skip(path, Skip::Empty);
+            self.skipped.add(Skip::Empty, shown_path(root, path));
+            return None;
+        }
+        if root.filter.skip_secrets() && redact::contains_private_key(&content) {
+            log_skip(path, Skip::Secret);
+            self.skipped.add(Skip::Secret, shown_path(root, path));
+            return None;
+        }
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        let mut redacted = 0;
+        if self.redact {
+            let (masked, count) = redact::redact(&content, &file_name);
+            if count > 0 {
+                info!("已遮盖 {} 中的 {} 处疑似密钥", path.display(), count);
+                self.redacted.fetch_add(count, Ordering::Relaxed);
+                content = masked;
+                redacted = count;
+            }
+        }
+
+        let language = if pdf { "text".to_string() } else { self.language_of(path, &content) };
+
+        if self.strip_comments {
+            content = compact::strip_comments(&content, &language);
+        }
+        if self.compact {
+            content = compact::compact_whitespace(&content);
+        }
+        if self.outline {
+            let extension = path.extension().unwrap_or_default().to_string_lossy().to_lowercase();
+            if let Some(outline) = outline::outline(&content, &extension) {
+                content = outline;
+            }
+        }
+        // 明确指定了行号范围的文件不再按行数截断
+        let max_lines = self.max_lines.filter(|_| ranges.is_none());
+        if let Some(truncated) = max_lines.and_then(|n| compact::head_tail(&content, n)) {
+            debug!("截断 {}：只保留首尾共 {} 行", path.display(), self.max_lines.unwrap_or(0));
+            content = truncated;
+        }
+
+        debug!("收录 {}（{}，{} 字节）", path.display(), language, size);
+        let file = CachedFile { size, modified, language, content, redacted };
+        if self.cache_path.is_some() && modified.is_some() {
+            self.fresh_cache.lock().unwrap().insert(key, file.clone());
+        }
+        Some(self.entry(root, path, file))
+    }
+
+    /// 较大的文件只预先流式扫描一遍，内容在写文档时才读取，不进入缓存。
+    /// 外层 None 表示文件含私钥块，须改为读入内存处理
+    fn stream_entry(&self, root: &Root, path: &Path) -> Option<Option<FileEntry>> {
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        let scanned = match Stream::scan(path, self.normalize_eol, self.redact.then(|| file_name.to_string())) {
+            Ok(scanned) => scanned,
+            Err(e) => {
+                warn!("无法读取 {}：{}", path.display(), e);
+                self.skipped.add(Skip::Unreadable, shown_path(root, path));
+                return Some(None);
+            }
+        };
+        let (stream, redacted, first_line, mixed_eol) = match scanned {
+            Scanned::Empty => {
+                log_skip(path, Skip::Empty);
+                self.skipped.add(Skip::Empty, shown_path(root, path));
+                return Some(None);
+            }
+            Scanned::PrivateKey => return None,
+            Scanned::Ready { stream, redacted, first_line, mixed_eol } => (stream, redacted, first_line, mixed_eol),
+        };
+        if mixed_eol && self.normalize_eol {
+            warn!("{} 混用了 CRLF 与 LF 换行", path.display());
+        }
+        if redacted > 0 {
+            info!("已遮盖 {} 中的 {} 处疑似密钥", path.display(), redacted);
+            self.redacted.fetch_add(redacted, Ordering::Relaxed);
+        }
+        let metadata = fs::metadata(pathname::extended(path)).ok();
+        let size = metadata.as_ref().map_or(0, |m| m.len());
+        let modified = metadata.and_then(|m| m.modified().ok());
+        let language = self.language_of(path, &first_line);
+        debug!("收录 {}（{}，{} 字节，写出时流式读取）", path.display(), language, size);
+        let file = CachedFile { size, modified, language, content: String::new(), redacted };
+        let mut entry = self.entry(root, path, file);
+        entry.stream = Some(stream);
+        Some(Some(entry))
+    }
+
+    /// 依次按特殊文件名、shebang（仅无扩展名时）与扩展名确定语言
+    fn language_of(&self, path: &Path, content: &str) -> String {
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        let extension = extension_of(path);
+        lang::from_file_name(&file_name)
+            .or_else(|| if extension.is_empty() { lang::from_shebang(content) } else { None })
+            .map_or_else(|| lang::fence(&extension, &self.languages), str::to_string)
+    }
+
+    fn entry(&self, root: &Root, path: &Path, file: CachedFile) -> FileEntry {
+        FileEntry {
+            root: root.label.clone(),
+            rel_path: format!("{}{}", root.prefix, relative_path(&root.path, path)),
+            escaped_path: pathname::is_escaped(path.strip_prefix(&root.path).unwrap_or(path)),
+            language: file.language,
+            extension: extension_of(path),
+            size: file.size,
+            modified: file.modified,
+            content: file.content,
+            tokens: None,
+            duplicate_of: None,
+            placeholder: None,
+            stream: None,
+            sha256: None,
+            symbols: Vec::new(),
+            extracted: self.extract_pdf && filter::is_pdf(path),
+        }
+    }
+}
+
+/// 不带点的小写扩展名，由它得出代码块的语言标识
+fn extension_of(path: &Path) -> String {
+    path.extension().unwrap_or_default().to_string_lossy().to_lowercase()
+}
+
+/// 需要完整内容的功能（去重、token 预算与统计、只输出摘要、分卷、每个文件一页、去注释、大纲、符号索引、引用关系图、按行截断等）
+/// 都未开启、且输出 Markdown 时，较大的文件才可流式读取
+fn streamable(options: &ConversionOptions, format: Format) -> bool {
+    format == Format::Markdown
+        && !options.dedup
+        && options.budget.is_none()
+        && !options.count_tokens
+        && !options.token_summary
+        && !options.stats_only
+        && options.max_tokens.is_none()
+        && options.max_output_size.is_none()
+        && !options.split_files
+        && !options.strip_comments
+        && !options.compact
+        && !options.outline
+        && !(options.collapsible && options.dialect.quotes_collapsible())
+        && !options.symbols
+        && !options.import_graph
+        && options.max_lines_per_file.is_none()
+}
+
+/// 影响文件处理结果的选项；与缓存中记录的不同时缓存作废
+fn cache_settings(
+    redact: bool,
+    options: &ConversionOptions,
+    config: &Config,
+    languages: &HashMap<String, String>,
+    line_ranges: &HashMap<String, Vec<RangeInclusive<usize>>>,
+) -> String {
+    let mut languages: Vec<_> = languages.iter().collect();
+    languages.sort();
+    let mut line_ranges: Vec<_> = line_ranges.iter().collect();
+    line_ranges.sort_by(|a, b| a.0.cmp(b.0));
+    format!(
+        "redact={} skip_secrets={} strip_comments={} compact={} outline={} normalize_eol={} max_lines={:?} languages={:?} lines={:?}",
+        redact,
+        config.skip_secrets.unwrap_or(false),
+        options.strip_comments,
+        options.compact,
+        options.outline,
+        options.normalize_eol,
+        options.max_lines_per_file,
+        languages,
+        line_ranges
+    )
+}
+
+/// 未收录内容的文件在文档中的占位项：`Binary file, 4.20 MiB, skipped`
+fn placeholder_entry(root: &Root, path: &Path, reason: Skip) -> FileEntry {
+    let metadata = fs::metadata(pathname::extended(path)).ok();
+    let size = metadata.as_ref().map_or(0, |m| m.len());
+    let kind = match reason {
+        Skip::Binary => "Binary file",
+        _ => "Over size limit",
+    };
+    FileEntry {
+        root: root.label.clone(),
+        rel_path: format!("{}{}", root.prefix, relative_path(&root.path, path)),
+        escaped_path: pathname::is_escaped(path.strip_prefix(&root.path).unwrap_or(path)),
+        language: String::new(),
+        extension: extension_of(path),
+        size,
+        modified: metadata.and_then(|m| m.modified().ok()),
+        content: String::new(),
+        tokens: None,
+        duplicate_of: None,
+        placeholder: Some(format!("{}, {}, skipped", kind, HumanBytes(size))),
+        stream: None,
+        sha256: None,
+        symbols: Vec::new(),
+        extracted: false,
+    }
+}
+
+/// 按原因分级记录被跳过的文件：名称与扩展名规则命中的文件很多，只在 -vv 时显示
+fn log_skip(path: &Path, reason: Skip) {
+    match reason {
+        Skip::Ignored | Skip::NotIncluded | Skip::Extension => debug!("跳过 {}：{}", path.display(), reason.describe()),
+        Skip::Unreadable => warn!("跳过 {}：{}", path.display(), reason.describe()),
+        _ => info!("跳过 {}：{}", path.display(), reason.describe()),
+    }
+}
+
+/// 多个路径指向同一文件时只保留一个：优先保留文件的真实位置，否则保留第一次出现的链接
+fn dedup_by_target(candidates: &mut Vec<(usize, PathBuf)>) {
+    let targets: Vec<Option<PathBuf>> = candidates.iter().map(|(_, p)| p.canonicalize().ok()).collect();
+    let real: HashSet<&PathBuf> = candidates
+        .iter()
+        .zip(&targets)
+        .filter_map(|((_, path), target)| target.as_ref().filter(|t| *t == path))
+        .collect();
+
+    let mut seen: HashSet<&PathBuf> = HashSet::new();
+    let keep: Vec<bool> = candidates
+        .iter()
+        .zip(&targets)
+        .map(|((_, path), target)| match target {
+            Some(t) if t == path => seen.insert(t),
+            Some(t) => !real.contains(t) && seen.insert(t),
+            None => true,
+        })
+        .collect();
+
+    let mut keep = keep.into_iter();
+    candidates.retain(|_| keep.next().unwrap_or(true));
+}
+
+/// 规范化给出的文件列表，去掉不存在或不在任何项目目录中的项
+fn canonical_files(files: &[PathBuf]) -> Vec<PathBuf> {
+    files
+        .iter()
+        .filter_map(|path| match path.canonicalize() {
+            Ok(canonical) => Some(canonical),
+            Err(e) => {
+                warn!("跳过 {}：{}", path.display(), e);
+                None
+            }
+        })
+        .collect()
+}
+
+fn common_ancestor(paths: &[PathBuf]) -> PathBuf {
+    let mut base = paths[0].clone();
+    for path in &paths[1..] {
+        while !path.starts_with(&base) {
+            if !base.pop() { break; }
+        }
+    }
+    base
+}
+
+/// 默认的输出文件：项目目录旁（save_inside 时为其中）以 name 或目录名命名、按格式加扩展名的文件；
+/// name 已带有该扩展名时不再重复添加
+fn output_path_for(source_path: &Path, name: Option<&str>, save_inside: bool, format: Format) -> PathBuf {
+    let stem = name.map_or_else(|| project_name(source_path), str::to_string);
+    let extension = format!(".{}", format.extension());
+    let file_name = if stem.ends_with(&extension) { stem } else { format!("{}{}", stem, extension) };
+
+    if source_path.is_dir() && save_inside {
+        source_path.join(file_name)
+    } else {
+        source_path.parent().unwrap_or(source_path).join(file_name)
+    }
+}
+
+/// 每个文件一页时的默认输出目录：`project.md` -> `project-md/`，避免与项目目录本身重名
+fn pages_dir_for(output: &Path) -> PathBuf {
+    let stem = output.file_stem().unwrap_or_default().to_string_lossy();
+    output.with_file_name(format!("{}-md", stem))
+}
+
+/// 输出文件可能尚不存在，因此只规范化其父目录，以便与遍历到的路径比较
+fn normalize_output(path: &Path) -> io::Result<PathBuf> {
+    let abs = std::path::absolute(path)?;
+    let parent = abs.parent().and_then(|p| p.canonicalize().ok());
+    match (parent, abs.file_name()) {
+        (Some(parent), Some(name)) => Ok(parent.join(name)),
+        _ => Ok(abs),
+    }
+}
+
+pub fn relative_path(source_path: &Path, path: &Path) -> String {
+    let rel_path = path.strip_prefix(source_path).unwrap_or(path);
+    pathname::display(rel_path)
+}
+
+/// 带根目录前缀的路径，用作缓存、--lines 与交互选择的键
+fn display_path(root: &Root, path: &Path) -> String {
+    let rel = relative_path(&root.path, path);
+    if root.label.is_empty() { rel } else { format!("{}/{}", root.label, rel) }
+}
+
+/// 文档中显示的路径：在 display_path 的相对路径前再加上 Root::prefix，与 FileEntry::display_path 一致
+fn shown_path(root: &Root, path: &Path) -> String {
+    let rel = format!("{}{}", root.prefix, relative_path(&root.path, path));
+    if root.label.is_empty() { rel } else { format!("{}/{}", root.label, rel) }
+}
+
+/// 把形如 `src/**/*.rs` 的输入拆成第一个含通配符的部分之前的目录与其后的相对模式。
+/// 路径确实存在、不含通配符或是远程仓库地址时返回 None；Windows 的 shell 不展开通配符，由这里代劳
+fn split_glob(input: &Path) -> Option<(PathBuf, String)> {
+    if input.exists() || input.to_str().is_some_and(git::is_remote_url) {
+        return None;
+    }
+    let mut base = PathBuf::new();
+    let mut pattern = Vec::new();
+    for component in input.components() {
+        let part = component.as_os_str().to_str()?;
+        if pattern.is_empty() && !part.contains(['*', '?', '[', '{']) {
+            base.push(component);
+        } else {
+            pattern.push(part);
+        }
+    }
+    if pattern.is_empty() {
+        return None;
+    }
+    if base.as_os_str().is_empty() {
+        base.push(".");
+    }
+    Some((base, pattern.join("/")))
+}
+
+/// 根目录的显示名称：沿用命令行中的写法，"." 之类则取目录名
+fn root_label(input: &Path, canonical: &Path) -> String {
+    let text = input.to_string_lossy().replace('\\', "/");
+    let text = text.trim_start_matches("./").trim_end_matches('/');
+    if text.is_empty() || text == "." || text == ".." || text.starts_with("../") {
+        project_name(canonical)
+    } else {
+        text.to_string()
+    }
+}
+
+fn project_name(source_path: &Path) -> String {
+    source_path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "project".to_string())
+}
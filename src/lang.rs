@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use syntect::parsing::SyntaxSet;
+
+/// syntect 自带的语法定义，HTML 高亮与语言识别共用
+pub fn syntax_set() -> &'static SyntaxSet {
+    static SET: OnceLock<SyntaxSet> = OnceLock::new();
+    SET.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+/// 按扩展名识别的语言名称，如 rs -> Rust；无法识别时为 None
+pub fn name(extension: &str) -> Option<&'static str> {
+    if extension.is_empty() {
+        return None;
+    }
+    syntax_set().find_syntax_by_extension(extension).map(|s| s.name.as_str())
+}
+
+/// 扩展名到代码块语言标识的内置映射，未列出的扩展名原样使用
+const FENCES: &[(&str, &str)] = &[
+    ("rs", "rust"),
+    ("py", "python"),
+    ("pyi", "python"),
+    ("rb", "ruby"),
+    ("js", "javascript"),
+    ("mjs", "javascript"),
+    ("cjs", "javascript"),
+    ("jsx", "jsx"),
+    ("ts", "typescript"),
+    ("mts", "typescript"),
+    ("cts", "typescript"),
+    ("tsx", "tsx"),
+    ("kt", "kotlin"),
+    ("kts", "kotlin"),
+    ("java", "java"),
+    ("scala", "scala"),
+    ("groovy", "groovy"),
+    ("gradle", "groovy"),
+    ("go", "go"),
+    ("c", "c"),
+    ("h", "c"),
+    ("cc", "cpp"),
+    ("cpp", "cpp"),
+    ("cxx", "cpp"),
+    ("hh", "cpp"),
+    ("hpp", "cpp"),
+    ("hxx", "cpp"),
+    ("cs", "csharp"),
+    ("fs", "fsharp"),
+    ("m", "objectivec"),
+    ("mm", "objectivec"),
+    ("swift", "swift"),
+    ("dart", "dart"),
+    ("php", "php"),
+    ("pl", "perl"),
+    ("pm", "perl"),
+    ("lua", "lua"),
+    ("r", "r"),
+    ("jl", "julia"),
+    ("ex", "elixir"),
+    ("exs", "elixir"),
+    ("erl", "erlang"),
+    ("hs", "haskell"),
+    ("ml", "ocaml"),
+    ("clj", "clojure"),
+    ("zig", "zig"),
+    ("nim", "nim"),
+    ("sh", "bash"),
+    ("bash", "bash"),
+    ("zsh", "zsh"),
+    ("fish", "fish"),
+    ("ps1", "powershell"),
+    ("psm1", "powershell"),
+    ("bat", "batch"),
+    ("cmd", "batch"),
+    ("yml", "yaml"),
+    ("yaml", "yaml"),
+    ("toml", "toml"),
+    ("json", "json"),
+    ("jsonc", "jsonc"),
+    ("xml", "xml"),
+    ("xaml", "xml"),
+    ("csproj", "xml"),
+    ("html", "html"),
+    ("htm", "html"),
+    ("vue", "vue"),
+    ("svelte", "svelte"),
+    ("css", "css"),
+    ("scss", "scss"),
+    ("sass", "sass"),
+    ("less", "less"),
+    ("sql", "sql"),
+    ("graphql", "graphql"),
+    ("gql", "graphql"),
+    ("proto", "protobuf"),
+    ("tf", "hcl"),
+    ("hcl", "hcl"),
+    ("ini", "ini"),
+    ("cfg", "ini"),
+    ("dockerfile", "dockerfile"),
+    ("cmake", "cmake"),
+    ("mk", "makefile"),
+    ("tex", "latex"),
+    ("md", "markdown"),
+    ("rst", "rst"),
+    ("adoc", "asciidoc"),
+    ("txt", "text"),
+];
+
+/// 代码块的语言标识：配置中的映射优先，其次是内置表，最后退回扩展名本身
+pub fn fence(extension: &str, overrides: &HashMap<String, String>) -> String {
+    if let Some(language) = overrides.get(extension) {
+        return language.clone();
+    }
+    FENCES
+        .iter()
+        .find(|(ext, _)| *ext == extension)
+        .map_or_else(|| extension.to_string(), |(_, language)| language.to_string())
+}
+
+/// 从 shebang 推断无扩展名脚本的语言：`#!/usr/bin/env python3` -> python
+pub fn from_shebang(content: &str) -> Option<&'static str> {
+    let line = content.lines().next()?.strip_prefix("#!")?;
+    let mut words = line.split_whitespace();
+    let mut program = words.next()?.rsplit('/').next()?;
+    // env 后面跟着的才是解释器，跳过 -S 之类的选项
+    if program == "env" {
+        program = words.find(|w| !w.starts_with('-'))?;
+    }
+    let interpreter = program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+
+    Some(match interpreter {
+        "python" | "pypy" => "python",
+        "sh" | "bash" | "dash" | "ash" | "ksh" => "bash",
+        "zsh" => "zsh",
+        "fish" => "fish",
+        "node" | "nodejs" => "javascript",
+        "deno" | "ts-node" | "tsx" | "bun" => "typescript",
+        "ruby" => "ruby",
+        "perl" => "perl",
+        "php" => "php",
+        "lua" | "luajit" => "lua",
+        "Rscript" => "r",
+        "pwsh" | "powershell" => "powershell",
+        "awk" | "gawk" => "awk",
+        "tclsh" | "wish" => "tcl",
+        "make" => "makefile",
+        _ => return None,
+    })
+}
+
+/// 按约定俗成的文件名识别语言，优先于扩展名（如 CMakeLists.txt）
+pub fn from_file_name(name: &str) -> Option<&'static str> {
+    let lower = name.to_ascii_lowercase();
+    if lower == "dockerfile" || lower == "containerfile" || lower.starts_with("dockerfile.") {
+        return Some("dockerfile");
+    }
+    Some(match lower.as_str() {
+        "makefile" | "gnumakefile" => "makefile",
+        "jenkinsfile" => "groovy",
+        "cmakelists.txt" => "cmake",
+        "rakefile" | "gemfile" | "vagrantfile" | "podfile" | "brewfile" => "ruby",
+        "build" | "build.bazel" | "workspace" | "workspace.bazel" | "tiltfile" => "starlark",
+        ".bashrc" | ".bash_profile" | ".profile" | ".zshrc" => "bash",
+        ".gitignore" | ".dockerignore" | ".gitattributes" => "gitignore",
+        _ => return None,
+    })
+}
@@ -0,0 +1,512 @@
+//! 将项目源码整理为单个 Markdown（或 JSON、HTML 等）文档。
+//!
+//! 命令行程序只是 [`convert`] 之上的一层参数解析，嵌入其他工具时直接调用即可：
+//!
+//! ```no_run
+//! let options = code2xml::ConversionOptions { stdout: true, ..Default::default() };
+//! let report = code2xml::convert("path/to/project", &options)?;
+//! eprintln!("收录 {} 个文件", report.entries.len());
+//! # Ok::<(), code2xml::Error>(())
+//! ```
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+mod anchor;
+mod archive;
+mod budget;
+mod cache;
+pub mod check;
+mod compact;
+pub mod config;
+mod dedup;
+mod deps;
+pub mod dialect;
+mod encoding;
+mod error;
+pub mod explain;
+pub mod extract;
+mod filter;
+mod front_matter;
+pub mod format;
+mod git;
+pub mod job;
+mod lang;
+pub mod locale;
+pub mod manifest;
+mod outline;
+mod pages;
+mod pathname;
+mod pdf;
+pub mod progress;
+mod redact;
+pub mod render;
+pub mod sort;
+mod sha256;
+mod split;
+mod stream;
+pub mod stats;
+pub mod tokens;
+pub mod tree;
+
+pub use dialect::Dialect;
+pub use error::{Error, Result};
+pub use format::Format;
+pub use locale::Locale;
+pub use render::FileEntry;
+pub use sort::SortOrder;
+
+use job::Job;
+use progress::{CountingWriter, Progress};
+use render::RenderOptions;
+use split::Limit;
+use stats::Skip;
+
+/// 一次转换的全部选项，与命令行参数一一对应；未设置的项沿用配置文件或默认值
+#[derive(Debug, Clone)]
+pub struct ConversionOptions {
+    /// 输出文件保存在项目目录内部（默认保存在其父目录）
+    pub save_inside: bool,
+    /// 写到标准输出而不是文件
+    pub stdout: bool,
+    /// 不写任何文件；没有 stdout、capture 时生成的文档直接丢弃
+    pub no_file: bool,
+    /// 输出文件路径，"-" 表示标准输出
+    pub output: Option<PathBuf>,
+    /// 默认输出文件的文件名（不含目录），取代项目目录名
+    pub name: Option<String>,
+    /// 文档标题，取代项目目录名
+    pub title: Option<String>,
+    /// 显示路径相对于该目录而不是扫描的根目录，如扫描 monorepo/backend 时显示 backend/src/...
+    pub relative_to: Option<PathBuf>,
+    /// 加在每个显示路径前的前缀
+    pub path_prefix: Option<String>,
+    /// 额外排除的 glob 模式
+    pub exclude: Vec<String>,
+    /// 仅输出匹配的文件
+    pub include: Vec<String>,
+    pub format: Option<Format>,
+    /// 文件排列顺序，默认按路径
+    pub sort: Option<SortOrder>,
+    /// 只收录被 git 跟踪的文件
+    pub git_only: bool,
+    /// 只收录相对于该 git 提交有改动的文件
+    pub since: Option<String>,
+    /// 输入为远程仓库地址时克隆的分支或标签
+    pub branch: Option<String>,
+    /// 只收录这些文件（如 fd、git ls-files 的输出），取代目录遍历与名称层面的规则；
+    /// 扩展名、大小、二进制等内容层面的规则仍然适用
+    pub files: Option<Vec<PathBuf>>,
+    pub follow_symlinks: bool,
+    /// 收录所有 .md 文件（默认只收录 README.md）
+    pub include_md: bool,
+    /// 收录 . 开头的目录（.git 等内置忽略目录除外）
+    pub hidden: bool,
+    /// 收录较小的 PDF，内容为从中提取的纯文本
+    pub extract_pdf: bool,
+    pub max_depth: Option<usize>,
+    /// 单个文件的大小上限（字节），0 表示不限
+    pub max_file_size: Option<u64>,
+    /// 按 token 数分卷
+    pub max_tokens: Option<usize>,
+    /// 按字节数分卷
+    pub max_output_size: Option<u64>,
+    /// 文件内容的 token 总数上限，超出时按优先级截断或省略文件
+    pub budget: Option<usize>,
+    /// 每个文件写成输出目录下的一页，另加 index.md（仅 Markdown）
+    pub split_files: bool,
+    /// 在文档旁另写清单（见 manifest::write），仅限写到文件的单个 Markdown 文档
+    pub manifest: bool,
+    pub tree: bool,
+    /// 在目录树之后加入文件间 use/import/include 关系的 Mermaid 图（仅 Markdown）
+    pub import_graph: bool,
+    pub toc: bool,
+    /// 在目录之后加入顶层定义的符号索引，链接到所在文件（仅 Markdown）
+    pub symbols: bool,
+    /// 按目录输出二级标题，文件标题嵌套在其下（仅 Markdown）
+    pub group_by_dir: bool,
+    /// 代码块包在 <details> 中，GitHub 等查看器默认折叠（仅 Markdown）
+    pub collapsible: bool,
+    /// 锚点、内部链接与折叠代码块按哪种查看器的 Markdown 写（仅 Markdown）
+    pub dialect: Dialect,
+    /// 标题等固定文字的语言；None 时按系统语言选择
+    pub locale: Option<Locale>,
+    /// 在文档开头加入 YAML front matter（仅 Markdown）
+    pub front_matter: bool,
+    /// 放在文档最前面（front matter 之后）的说明文字；为已存在的文件路径时读取其内容
+    pub preamble: Option<String>,
+    /// 文件标题的级别（1–6），默认 2
+    pub heading_level: Option<usize>,
+    /// 文件标题的模板，{path} 处替换为路径，默认为所选语言的 "File: {path}"
+    pub heading: Option<String>,
+    pub line_numbers: bool,
+    pub metadata: bool,
+    /// 为每个文件注明内容的 SHA-256，并在末尾附上清单（Markdown；JSON 中为 sha256 字段）
+    pub hashes: bool,
+    pub summary: bool,
+    /// 只输出项目摘要（含 token 估算），不含文件内容；仅 Markdown
+    pub stats_only: bool,
+    /// 在文档末尾列出被跳过的文件及原因（仅 Markdown）
+    pub skipped_files: bool,
+    /// 二进制与过大的文件仍输出标题及一行说明，而不是直接省略
+    pub skipped_inline: bool,
+    /// 统计每个文件的 token 数，结果见 FileEntry::tokens
+    pub count_tokens: bool,
+    /// 在文档中加入 token 统计表（隐含 count_tokens）
+    pub token_summary: bool,
+    pub redact: bool,
+    pub skip_secrets: bool,
+    pub strip_comments: bool,
+    pub compact: bool,
+    /// 只保留定义所在的行，得到代码的大纲
+    pub outline: bool,
+    /// 把输出内容中的 CRLF 换行统一为 LF，并对混用两者的文件给出警告
+    pub normalize_eol: bool,
+    /// 超过该行数的文件只输出开头与结尾各一半，中间注明省略的行数
+    pub max_lines_per_file: Option<usize>,
+    /// 只收录某些文件中的指定行：(显示路径, "1-200,900-1000")，覆盖配置文件中同一文件的设置
+    pub lines: Vec<(String, String)>,
+    /// 内容相同的文件只输出第一个，其余改为引用
+    pub dedup: bool,
+    /// 在 Report::document 中返回生成的文档。此时只有显式给出 output 或 save_inside 才会同时写文件
+    pub capture: bool,
+    /// 只收录这些文件（文档中的显示路径，通常来自 scan 后的手动选择），取代内容层面的过滤规则。
+    /// 用显示路径而非绝对路径，压缩包与远程仓库每次解压、克隆到的临时目录不同也不受影响
+    pub selection: Option<Vec<String>>,
+    /// 在 stderr 上显示进度条
+    pub progress: bool,
+    /// 在项目目录的 .code2md-cache 中记录处理过的文件，未变化的文件下次不再读取
+    pub cache: bool,
+}
+
+impl Default for ConversionOptions {
+    fn default() -> Self {
+        ConversionOptions {
+            save_inside: false,
+            stdout: false,
+            no_file: false,
+            output: None,
+            name: None,
+            title: None,
+            relative_to: None,
+            path_prefix: None,
+            exclude: Vec::new(),
+            include: Vec::new(),
+            format: None,
+            sort: None,
+            git_only: false,
+            since: None,
+            branch: None,
+            files: None,
+            follow_symlinks: false,
+            include_md: false,
+            extract_pdf: false,
+            hidden: false,
+            max_depth: None,
+            max_file_size: None,
+            max_tokens: None,
+            max_output_size: None,
+            budget: None,
+            split_files: false,
+            manifest: false,
+            tree: true,
+            import_graph: false,
+            toc: true,
+            symbols: false,
+            group_by_dir: false,
+            collapsible: false,
+            dialect: Dialect::Gfm,
+            locale: None,
+            front_matter: false,
+            preamble: None,
+            heading_level: None,
+            heading: None,
+            line_numbers: false,
+            metadata: false,
+            hashes: false,
+            summary: false,
+            stats_only: false,
+            skipped_files: false,
+            skipped_inline: false,
+            count_tokens: false,
+            token_summary: false,
+            redact: true,
+            skip_secrets: false,
+            strip_comments: false,
+            compact: false,
+            outline: false,
+            normalize_eol: false,
+            max_lines_per_file: None,
+            lines: Vec::new(),
+            dedup: false,
+            capture: false,
+            selection: None,
+            progress: false,
+            cache: true,
+        }
+    }
+}
+
+/// 一次转换的结果
+pub struct Report {
+    /// 写出的文件（分卷时为各卷）；写到标准输出时为空
+    pub outputs: Vec<PathBuf>,
+    /// 收录的文件，按文档中的顺序；含 skipped_inline 时的占位项
+    pub entries: Vec<FileEntry>,
+    /// 写出的总字节数
+    pub written: u64,
+    /// 已遮盖的疑似密钥处数
+    pub redacted: usize,
+    /// 因疑似密钥而跳过的文件
+    pub skipped_secrets: Vec<String>,
+    /// 为满足 token 预算而截断的文件
+    pub truncated: Vec<String>,
+    /// 为满足 token 预算而整个省略的文件
+    pub omitted: Vec<String>,
+    /// 生成的文档，仅在 capture 时存在
+    pub document: Option<String>,
+}
+
+/// scan 的结果之一
+pub struct ScanEntry {
+    /// 文档中的显示路径，即 ConversionOptions::selection 中使用的路径；被忽略的目录以 / 结尾
+    pub display: String,
+    /// 文件大小（字节），目录为 0
+    pub size: u64,
+    /// 按当前规则未被收录的原因，None 为会被收录
+    pub skipped: Option<Skip>,
+}
+
+impl ScanEntry {
+    pub fn included(&self) -> bool {
+        self.skipped.is_none()
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.display.ends_with('/')
+    }
+}
+
+/// 列出遍历到的全部文件及其是否会被收录，不读取内容也不写文档。
+/// 被名称规则忽略的文件与目录也在其中；空文件与无法读取的文件要读取时才知道，这里仍算作收录。
+/// 手动调整后可把选中的文件放进 ConversionOptions::selection 再调用 convert_all
+pub fn scan(paths: &[PathBuf], options: &ConversionOptions) -> Result<Vec<ScanEntry>> {
+    let job = Job::new(paths, options, Progress::new(false))?;
+    Ok(job
+        .scan()?
+        .into_iter()
+        .map(|(index, path, skipped)| {
+            let metadata = fs::metadata(&path).ok();
+            let mut display = job.display_path(index, &path);
+            if metadata.as_ref().is_some_and(|m| m.is_dir()) {
+                display.push('/');
+            }
+            let size = metadata.filter(|m| m.is_file()).map_or(0, |m| m.len());
+            ScanEntry { display, size, skipped }
+        })
+        .collect())
+}
+
+/// 转换单个项目目录、压缩包或远程仓库地址
+pub fn convert(path: impl AsRef<Path>, options: &ConversionOptions) -> Result<Report> {
+    convert_all(&[path.as_ref().to_path_buf()], options)
+}
+
+/// 把多个目录合并转换为一份文档，各目录的文件按根目录分组
+pub fn convert_all(paths: &[PathBuf], options: &ConversionOptions) -> Result<Report> {
+    let job = Job::new(paths, options, Progress::new(options.progress))?;
+    run(&job, options)
+}
+
+/// 按已建好的 Job 生成文档；是否写文件、写到哪里仍由 options 决定
+pub(crate) fn run(job: &Job, options: &ConversionOptions) -> Result<Report> {
+    let limit = match (options.max_tokens, options.max_output_size) {
+        (Some(n), _) => Some(Limit::Tokens(n)),
+        (None, Some(n)) => Some(Limit::Bytes(n as usize)),
+        (None, None) => None,
+    };
+    if limit.is_some() && job.output_path.is_none() {
+        return Err(Error::InvalidOptions("分卷输出不能写到标准输出".into()));
+    }
+    if limit.is_some() && job.format != Format::Markdown {
+        return Err(Error::InvalidOptions("分卷输出仅支持 Markdown 格式".into()));
+    }
+    if limit.is_some() && options.capture {
+        return Err(Error::InvalidOptions("分卷输出无法同时返回文档内容".into()));
+    }
+    if options.split_files && (job.output_path.is_none() || limit.is_some() || options.capture) {
+        return Err(Error::InvalidOptions("每个文件一页时只能写到输出目录，不能同时分卷、写到标准输出或返回文档内容".into()));
+    }
+    if options.split_files && job.format != Format::Markdown {
+        return Err(Error::InvalidOptions("每个文件一页仅支持 Markdown 格式".into()));
+    }
+    if options.manifest
+        && (job.output_path.is_none() || limit.is_some() || options.split_files || options.capture || job.format != Format::Markdown)
+    {
+        return Err(Error::InvalidOptions("清单只能为写到文件的单个 Markdown 文档生成".into()));
+    }
+    if options.capture && job.format.is_binary() {
+        return Err(Error::InvalidOptions(format!("{} 是二进制格式，不能复制到剪贴板或用于 check", job.format.extension().to_uppercase())));
+    }
+    if options.preamble.is_some() && job.format == Format::Json {
+        return Err(Error::InvalidOptions("JSON 格式不支持 --preamble".into()));
+    }
+    if options.stats_only && (limit.is_some() || options.split_files || options.manifest || job.format != Format::Markdown) {
+        return Err(Error::InvalidOptions("只输出摘要时不能分卷、每个文件一页或生成清单，且仅支持 Markdown 格式".into()));
+    }
+
+    let mut entries = job.load_entries()?;
+    job.save_cache();
+    if entries.is_empty() {
+        return Err(Error::NoFiles);
+    }
+    sort::sort_entries(&mut entries, job.sort);
+    if options.group_by_dir {
+        sort::group_by_dir(&mut entries);
+    }
+    if options.dedup {
+        dedup::mark_duplicates(&mut entries);
+    }
+    let (omitted, truncated): (Vec<_>, Vec<_>) = options.budget
+        .map(|n| budget::fit(&mut entries, n))
+        .unwrap_or_default()
+        .into_iter()
+        .partition(|t| t.dropped);
+    if options.count_tokens || options.token_summary || options.stats_only {
+        tokens::count_entries(&mut entries);
+    }
+    if options.hashes {
+        sha256::hash_entries(&mut entries)?;
+    }
+    if options.symbols {
+        outline::index_entries(&mut entries);
+    }
+
+    let preamble = options.preamble.as_deref().map(load_preamble).transpose()?;
+    let project_name = job.project_name();
+    let title = job.title();
+    let front_matter = options.front_matter.then(|| front_matter::render(&title, entries.iter().filter(|e| e.is_included()).count(), job, options));
+    let opts = RenderOptions {
+        project_name: &project_name,
+        title: &title,
+        tree: options.tree,
+        import_graph: options.import_graph,
+        toc: options.toc,
+        symbols: options.symbols,
+        token_summary: options.token_summary,
+        line_numbers: options.line_numbers,
+        metadata: options.metadata,
+        summary: (options.summary || options.stats_only).then_some(&*job.skipped),
+        skipped_files: options.skipped_files.then_some(&*job.skipped),
+        group_by_dir: options.group_by_dir,
+        collapsible: options.collapsible,
+        dialect: options.dialect,
+        locale: job.locale,
+        front_matter: front_matter.as_deref(),
+        preamble: preamble.as_deref(),
+        heading_level: job.heading_level,
+        heading_template: &job.heading_template,
+    };
+
+    let mut document = None;
+    let (outputs, written) = match (limit, &job.output_path) {
+        (None, Some(dir)) if options.split_files => {
+            fs::create_dir_all(dir).map_err(unwritable(Some(dir)))?;
+            let pages = pages::write_pages(dir, &entries, &opts).map_err(unwritable(Some(dir)))?;
+            let written = pages.iter().filter_map(|p| p.metadata().ok()).map(|m| m.len()).sum();
+            (pages, written)
+        }
+        (Some(limit), Some(output)) => {
+            if let Some(parent) = output.parent() {
+                fs::create_dir_all(parent).map_err(unwritable(Some(output)))?;
+            }
+            let parts = split::write_parts(output, &entries, &opts, limit).map_err(unwritable(Some(output)))?;
+            let written = parts.iter().filter_map(|p| p.metadata().ok()).map(|m| m.len()).sum();
+            (parts, written)
+        }
+        _ if options.capture => {
+            let mut buffer = Vec::new();
+            if options.stats_only {
+                render::write_stats(&mut buffer, &entries, &opts)?;
+            } else {
+                format::write(job.format, &mut buffer, &entries, &opts)?;
+            }
+            let outputs = if (options.output.is_some() || options.save_inside) && !options.no_file {
+                write_output(job.output_path.as_deref(), &buffer).map_err(unwritable(job.output_path.as_deref()))?;
+                job.output_path.iter().cloned().collect()
+            } else {
+                Vec::new()
+            };
+            let written = buffer.len() as u64;
+            document = Some(String::from_utf8_lossy(&buffer).into_owned());
+            (outputs, written)
+        }
+        _ => {
+            let output = job.output_path.as_deref();
+            let target = if options.no_file && !options.stdout { Box::new(io::sink()) } else { open_output(output).map_err(unwritable(output))? };
+            let mut writer = BufWriter::new(CountingWriter::new(target));
+            let spans = if options.stats_only {
+                render::write_stats(&mut writer, &entries, &opts).map(|_| Vec::new())
+            } else if options.manifest {
+                render::write_document(&mut writer, &entries, &render::sections(&entries, &opts), &opts, None)
+            } else {
+                format::write(job.format, &mut writer, &entries, &opts).map(|_| Vec::new())
+            };
+            let spans = spans.map_err(unwritable(output))?;
+            writer.flush().map_err(unwritable(output))?;
+            let written = writer.get_ref().count;
+            let mut outputs: Vec<PathBuf> = job.output_path.iter().cloned().collect();
+            if let (true, Some(output)) = (options.manifest, output) {
+                outputs.push(manifest::write(output, &entries, &spans).map_err(unwritable(Some(output)))?);
+            }
+            (outputs, written)
+        }
+    };
+
+    job.progress.finish(written);
+    Ok(Report {
+        outputs,
+        entries,
+        written,
+        redacted: job.redacted(),
+        skipped_secrets: job.skipped.secrets(),
+        truncated: truncated.into_iter().map(|t| t.path).collect(),
+        omitted: omitted.into_iter().map(|t| t.path).collect(),
+        document,
+    })
+}
+
+/// 打开输出文件（必要时创建父目录）；None 为标准输出
+fn open_output(path: Option<&Path>) -> io::Result<Box<dyn Write>> {
+    Ok(match path {
+        Some(path) => {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            Box::new(File::create(path)?)
+        }
+        None => Box::new(io::stdout().lock()),
+    })
+}
+
+/// --preamble 的值为已存在的文件时取其内容，否则就是说明文字本身；末尾多余的空行去掉
+fn load_preamble(value: &str) -> Result<String> {
+    let path = Path::new(value);
+    let text = if path.is_file() { fs::read_to_string(path)? } else { value.to_string() };
+    Ok(text.trim_end().to_string())
+}
+
+fn write_output(path: Option<&Path>, content: &[u8]) -> io::Result<()> {
+    let mut sink = open_output(path)?;
+    sink.write_all(content)?;
+    sink.flush()
+}
+
+/// 写输出时的错误：写文件失败归为 OutputUnwritable，标准输出（如管道被关闭）仍为 Io
+fn unwritable(path: Option<&Path>) -> impl FnOnce(io::Error) -> Error + '_ {
+    move |source| match path {
+        Some(path) => Error::OutputUnwritable { path: path.to_path_buf(), source },
+        None => Error::Io(source),
+    }
+}
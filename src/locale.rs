@@ -0,0 +1,184 @@
+use clap::ValueEnum;
+use serde::Deserialize;
+
+use crate::stats::Skip;
+
+/// 输出文档中固定文字（文件标题、目录树与摘要等小节名）所用的语言
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    Zh,
+}
+
+/// 一种语言下的固定文字
+pub struct Strings {
+    /// 默认的文件标题模板
+    pub heading_template: &'static str,
+    /// 文件标题中路径前的文字，Markdown 以外的格式直接使用
+    pub file_prefix: &'static str,
+    /// 多根目录时分组标题的前缀
+    pub root_prefix: &'static str,
+    /// --group-by-dir 的目录标题前缀
+    pub directory_prefix: &'static str,
+    /// 重复文件引用行的开头，其后为原文件路径
+    pub duplicate_prefix: &'static str,
+    /// 非 Markdown 格式中的重复文件说明，{path} 处替换为原文件
+    pub identical_to: &'static str,
+    pub tree: &'static str,
+    pub imports: &'static str,
+    pub toc: &'static str,
+    pub symbols: &'static str,
+    pub tokens: &'static str,
+    pub summary: &'static str,
+    pub skipped: &'static str,
+    pub hashes: &'static str,
+    pub files: &'static str,
+    /// 表格的列名
+    pub file: &'static str,
+    pub lines: &'static str,
+    pub size: &'static str,
+    pub token_count: &'static str,
+    pub language: &'static str,
+    pub reason: &'static str,
+    pub symbol: &'static str,
+    pub kind: &'static str,
+    pub total: &'static str,
+    /// 没有扩展名的文件归入的语言
+    pub other: &'static str,
+    /// 摘要中名称与数值之间的分隔
+    pub colon: &'static str,
+    /// 从 PDF 中提取的文字之前的说明
+    pub extracted: &'static str,
+    /// 列表为空时的说明
+    pub none: &'static str,
+    pub secrets: &'static str,
+}
+
+const EN: Strings = Strings {
+    heading_template: "File: {path}",
+    file_prefix: "File: ",
+    root_prefix: "Root: ",
+    directory_prefix: "Directory: ",
+    duplicate_prefix: "> Identical to ",
+    identical_to: "Identical to {path}.",
+    tree: "Directory Structure",
+    imports: "Import Graph",
+    toc: "Table of Contents",
+    symbols: "Symbol Index",
+    tokens: "Token Usage",
+    summary: "Summary",
+    skipped: "Skipped Files",
+    hashes: "SHA-256 Manifest",
+    files: "Files",
+    file: "File",
+    lines: "Lines",
+    size: "Size",
+    token_count: "Tokens",
+    language: "Language",
+    reason: "Reason",
+    symbol: "Symbol",
+    kind: "Kind",
+    total: "Total",
+    other: "Other",
+    colon: ": ",
+    extracted: "Text extracted from PDF:",
+    none: "None.",
+    secrets: "Skipped as likely secrets:",
+};
+
+const ZH: Strings = Strings {
+    heading_template: "文件：{path}",
+    file_prefix: "文件：",
+    root_prefix: "根目录：",
+    directory_prefix: "文件夹：",
+    duplicate_prefix: "> 内容与此文件相同：",
+    identical_to: "内容与 {path} 相同。",
+    tree: "目录结构",
+    imports: "引用关系",
+    toc: "目录",
+    symbols: "符号索引",
+    tokens: "Token 用量",
+    summary: "摘要",
+    skipped: "跳过的文件",
+    hashes: "SHA-256 校验清单",
+    files: "文件",
+    file: "文件",
+    lines: "行数",
+    size: "大小",
+    token_count: "Token 数",
+    language: "语言",
+    reason: "原因",
+    symbol: "符号",
+    kind: "类型",
+    total: "合计",
+    other: "其他",
+    colon: "：",
+    extracted: "从 PDF 中提取的文字：",
+    none: "无。",
+    secrets: "疑似密钥而跳过：",
+};
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::Zh];
+
+    pub fn strings(self) -> &'static Strings {
+        match self {
+            Locale::En => &EN,
+            Locale::Zh => &ZH,
+        }
+    }
+
+    /// BCP 47 语言标记，用于 EPUB 等格式的 lang 属性
+    pub fn tag(self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Zh => "zh",
+        }
+    }
+
+    /// 按系统语言选择：LC_ALL、LC_MESSAGES、LANG 中第一个非空的值，Windows 上为用户的区域设置。
+    /// 中文为 zh，其余为 en
+    pub fn detect() -> Locale {
+        let name = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|key| std::env::var(key).ok())
+            .find(|value| !value.is_empty())
+            .or_else(system_locale)
+            .unwrap_or_default();
+        if name.to_lowercase().starts_with("zh") { Locale::Zh } else { Locale::En }
+    }
+
+    /// 跳过原因在摘要与跳过清单中的说明
+    pub fn describe(self, reason: Skip) -> &'static str {
+        match self {
+            Locale::En => reason.describe(),
+            Locale::Zh => match reason {
+                Skip::Ignored => "忽略的名称或排除模式",
+                Skip::NotIncluded => "不匹配包含模式",
+                Skip::Extension => "忽略的扩展名",
+                Skip::TooLarge => "超过大小上限",
+                Skip::Binary => "二进制文件",
+                Skip::Empty => "空文件",
+                Skip::Unreadable => "无法读取",
+                Skip::Secret => "疑似密钥",
+            },
+        }
+    }
+}
+
+#[cfg(windows)]
+fn system_locale() -> Option<String> {
+    use windows_sys::Win32::Globalization::GetUserDefaultLocaleName;
+    // LOCALE_NAME_MAX_LENGTH
+    let mut buffer = [0u16; 85];
+    let len = unsafe { GetUserDefaultLocaleName(buffer.as_mut_ptr(), buffer.len() as i32) };
+    // 返回的长度包含结尾的 NUL
+    (len > 1).then(|| String::from_utf16_lossy(&buffer[..len as usize - 1]))
+}
+
+#[cfg(not(windows))]
+fn system_locale() -> Option<String> {
+    None
+}
@@ -0,0 +1,44 @@
+//! 诊断日志输出到 stderr。默认只显示警告（如无法读取的文件），
+//! -v 显示被跳过的文件及原因，-vv 显示每个文件的处理过程，-q 只显示错误。
+
+use std::io::{self, IsTerminal};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::Level;
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// 完成、已复制等提示行，-q 时不显示
+macro_rules! status {
+    ($($arg:tt)*) => {
+        if !crate::logging::quiet() {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
+pub fn init(verbose: u8, quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+    let level = match verbose {
+        _ if quiet => Level::ERROR,
+        0 => Level::WARN,
+        1 => Level::INFO,
+        2 => Level::DEBUG,
+        _ => Level::TRACE,
+    };
+    tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_target(false)
+        .without_time()
+        .with_ansi(io::stderr().is_terminal())
+        .with_writer(io::stderr)
+        .init();
+}
+
+/// 只在默认级别下显示进度条：详细日志会与进度条相互覆盖，-q 时也不应有多余输出
+pub fn progress_allowed() -> bool {
+    tracing::enabled!(Level::WARN) && !tracing::enabled!(Level::INFO)
+}
+
+pub fn quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
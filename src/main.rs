@@ -1,188 +1,296 @@
 #![windows_subsystem = "windows"]
 
-use std::collections::HashSet;
-use std::env;
-use std::fs::{self, File};
-use std::io::{self, BufWriter, Read, Write};
+use std::fs;
+use std::io::{self, Write};
 use std::path::{Path, PathBuf};
-use std::sync::OnceLock;
-use walkdir::{DirEntry, WalkDir};
-
-// --- 忽略配置 ---
-fn get_ignore_dirs() -> &'static HashSet<&'static str> {
-    static DIRS: OnceLock<HashSet<&'static str>> = OnceLock::new();
-    DIRS.get_or_init(|| {
-        HashSet::from([
-            ".git", ".idea", ".vscode", ".vs", "__pycache__", "node_modules", 
-            "venv", ".venv", "env", "dist", "build", "target", "out", 
-            "bin", "obj", "debug", "release", 
-            ".gradle", "captures", "gradle", ".DS_Store", "coverage", ".next", ".nuxt"
-        ])
-    })
-}
+use clap::{CommandFactory, Parser};
+use indicatif::HumanBytes;
 
-fn get_ignore_filenames() -> &'static HashSet<&'static str> {
-    static FILES: OnceLock<HashSet<&'static str>> = OnceLock::new();
-    FILES.get_or_init(|| {
-        HashSet::from([
-            "gradlew", "gradlew.bat", "mvnw", "mvnw.cmd",
-            "local.properties", "thumbs.db", "desktop.ini", 
-            "package-lock.json", "yarn.lock", "pnpm-lock.yaml", "cargo.lock", "poetry.lock"
-        ])
-    })
-}
+use code2xml::job::Job;
+use code2xml::progress::Progress;
+use code2xml::{check, explain, extract, manifest, tokens, ConversionOptions, Error, Format, Locale, Report, Result};
 
-fn get_ignore_extensions() -> &'static HashSet<&'static str> {
-    static EXTS: OnceLock<HashSet<&'static str>> = OnceLock::new();
-    EXTS.get_or_init(|| {
-        HashSet::from([
-            // 媒体文件
-            ".png", ".jpg", ".jpeg", ".gif", ".bmp", ".ico", ".svg", ".webp", ".tiff",
-            ".mp3", ".mp4", ".wav", ".avi", ".mov",
-            // 二进制/压缩包
-            ".exe", ".dll", ".so", ".dylib", ".bin", ".apk", ".aab", ".jar", ".war",
-            ".zip", ".tar", ".gz", ".7z", ".rar", ".iso", ".cab",
-            // 编译中间产物
-            ".pyc", ".class", ".o", ".obj", ".pdb", ".suo",
-            ".db", ".sqlite", ".sqlite3", ".lock", ".log",
-            // 新增：忽略 md 文件，避免递归处理或包含说明文档
-            ".md"
-        ])
-    })
-}
+mod cli;
+mod completions;
+mod console;
+mod gui;
+#[macro_use]
+mod logging;
+mod mcp;
+mod serve;
+mod shell;
+mod tui;
+mod watch;
 
-struct Args {
-    path: String,
-    save_inside: bool,
-}
+use cli::{Cli, Command, ExplainArgs, ExtractArgs, GenerateArgs, ServeArgs, VerifyArgs};
 
-fn parse_args() -> Option<Args> {
-    let args: Vec<String> = env::args().collect();
-    if args.len() < 2 {
-        return None;
+fn run_generate(args: &GenerateArgs) -> Result<()> {
+    let mut options = args.options();
+    if args.interactive {
+        let Some(selection) = tui::select(code2xml::scan(&args.paths, &options)?)? else {
+            status!("已取消");
+            return Ok(());
+        };
+        options.selection = Some(selection);
     }
-
-    let path = args[1].clone();
-    let save_inside = args.iter().any(|arg| arg == "-i");
-
-    Some(Args { path, save_inside })
+    let report = code2xml::convert_all(&args.paths, &options)?;
+    if let Some(document) = &report.document {
+        copy_to_clipboard(document)?;
+        status!("已复制到剪贴板（{} 个字符）", document.chars().count());
+    }
+    print_report(args, &report)?;
+    Ok(())
 }
 
-fn is_hidden_or_ignored(entry: &DirEntry) -> bool {
-    let file_name = entry.file_name().to_str().unwrap_or("");
-    
-    if entry.file_type().is_dir() {
-        if file_name.starts_with('.') && file_name.len() > 1 && file_name != ".github" {
-            return true;
+/// 先列出会被收录的文件，再列出被跳过的文件及原因，最后给出合计；
+/// 与文档一样按 --locale 或系统语言输出，不混用两种语言
+fn run_dry_run(args: &GenerateArgs) -> Result<()> {
+    let locale = args.locale.unwrap_or_else(Locale::detect);
+    let mut entries = code2xml::scan(&args.paths, &args.options())?;
+    entries.sort_by(|a, b| a.display.cmp(&b.display));
+    let (included, skipped): (Vec<_>, Vec<_>) = entries.iter().partition(|e| e.included());
+    let width = entries.iter().map(|e| e.display.chars().count()).max().unwrap_or(0);
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    for entry in &included {
+        writeln!(out, "{:>10}  {}", HumanBytes(entry.size).to_string(), entry.display)?;
+    }
+    if !skipped.is_empty() {
+        writeln!(out, "\n{}{}", locale.strings().skipped, locale.strings().colon.trim_end())?;
+        for entry in &skipped {
+            let size = if entry.is_dir() { String::new() } else { HumanBytes(entry.size).to_string() };
+            let reason = entry.skipped.map_or("", |reason| locale.describe(reason));
+            writeln!(out, "{:>10}  {:<width$}  {}", size, entry.display, reason)?;
         }
-        if get_ignore_dirs().contains(file_name) { return true; }
-    } else {
-        if get_ignore_filenames().contains(&file_name.to_lowercase().as_str()) { return true; }
     }
-    false
+    let total: u64 = included.iter().map(|e| e.size).sum();
+    let (count, total, skipped) = (included.len(), HumanBytes(total), skipped.len());
+    match locale {
+        Locale::En => writeln!(out, "\nWould include {} files, {} in total; skipping {}", count, total, skipped)?,
+        Locale::Zh => writeln!(out, "\n将收录 {} 个文件，共 {}；跳过 {} 项", count, total, skipped)?,
+    }
+    Ok(())
 }
 
-fn is_text_file(path: &Path) -> bool {
-    let mut file = match File::open(path) {
-        Ok(f) => f,
-        Err(_) => return false,
-    };
-    
-    let mut buffer = [0; 1024]; 
-    let n = match file.read(&mut buffer) {
-        Ok(n) => n,
-        Err(_) => return false,
-    };
-    if n == 0 { return true; }
+fn copy_to_clipboard(text: &str) -> io::Result<()> {
+    let mut clipboard = arboard::Clipboard::new().map_err(|e| io::Error::other(format!("无法访问剪贴板：{}", e)))?;
+    clipboard.set_text(text).map_err(|e| io::Error::other(format!("写入剪贴板失败：{}", e)))
+}
 
-    !buffer[..n].contains(&0)
+/// 进度条结束后再输出 stderr 上的报告，避免相互覆盖
+fn print_report(args: &GenerateArgs, report: &Report) -> io::Result<()> {
+    if !report.skipped_secrets.is_empty() {
+        status!("已跳过 {} 个疑似密钥文件（--summary 可在文档中列出）", report.skipped_secrets.len());
+    }
+    if !report.truncated.is_empty() || !report.omitted.is_empty() {
+        status!(
+            "为满足 token 预算，截断了 {} 个文件、省略了 {} 个文件",
+            report.truncated.len(),
+            report.omitted.len()
+        );
+    }
+    if report.redacted > 0 {
+        status!("已遮盖 {} 处疑似密钥（--no-redact 可关闭）", report.redacted);
+    }
+    if args.tokens || args.token_summary {
+        tokens::report(&mut io::stderr().lock(), &report.entries)?;
+    }
+    Ok(())
 }
 
-fn run_app() -> io::Result<()> {
-    let args = match parse_args() {
-        Some(a) => a,
-        None => return Ok(()),
-    };
+fn run_watch(args: &GenerateArgs) -> Result<()> {
+    run_generate(args)?;
 
-    let source_path = Path::new(&args.path).canonicalize()?;
-    
-    let name_os = source_path.file_name().unwrap_or(std::ffi::OsStr::new("项目代码文档"));
-    let folder_name = name_os.to_string_lossy();
-    
-    // 修改：扩展名改为 .md
-    let file_name = format!("{}.md", folder_name);
-
-    let output_path = if source_path.is_dir() {
-        if args.save_inside {
-            source_path.join(file_name)
-        } else {
-            source_path.parent().unwrap_or(&source_path).join(file_name)
-        }
-    } else {
-        source_path.parent().unwrap_or(&source_path).join(file_name)
+    let job = Job::new(&args.paths, &args.options(), Progress::hidden())?;
+    let is_relevant = |path: &Path| {
+        !job.is_own_output(path)
+            && !job.roots.iter().any(|r| path.starts_with(&r.path) && r.filter.is_inside_ignored_dir(path))
     };
+    let roots: Vec<PathBuf> = job.roots.iter().map(|r| r.path.clone()).collect();
+    watch::watch(&roots, is_relevant, || {
+        // 每次重新构建 Job，使配置文件的修改也能生效
+        if let Err(e) = run_generate(args) {
+            eprintln!("重新生成失败：{}", e);
+        }
+    })?;
+    Ok(())
+}
 
-    let file = File::create(&output_path)?;
-    let mut writer = BufWriter::new(file);
+/// 每次重新生成都重新读取选项与配置文件；文档只在内存中生成，不写文件也不复制到剪贴板
+fn run_serve(args: &ServeArgs) -> Result<()> {
+    let options = || ConversionOptions {
+        format: Some(Format::Html),
+        capture: true,
+        output: None,
+        save_inside: false,
+        stdout: false,
+        ..args.generate.options()
+    };
+    let render = || Ok(code2xml::convert_all(&args.generate.paths, &options())?.document.unwrap_or_default());
 
-    let out_file_name_os = output_path.file_name().unwrap_or_default();
-    let out_file_abs = output_path.canonicalize().unwrap_or_else(|_| output_path.clone());
+    let job = Job::new(&args.generate.paths, &options(), Progress::hidden())?;
+    let is_relevant = |path: &Path| {
+        !job.is_own_output(path)
+            && !job.roots.iter().any(|r| path.starts_with(&r.path) && r.filter.is_inside_ignored_dir(path))
+    };
+    let roots: Vec<PathBuf> = job.roots.iter().map(|r| r.path.clone()).collect();
+    serve::serve(args.port, &roots, is_relevant, render)
+}
 
-    let walker = WalkDir::new(&source_path).into_iter();
+fn run_list(args: &GenerateArgs) -> Result<()> {
+    let job = Job::new(&args.paths, &args.options(), Progress::hidden())?;
 
-    for entry in walker.filter_entry(|e| !is_hidden_or_ignored(e)) {
-        let entry = match entry { Ok(e) => e, Err(_) => continue };
-        let path = entry.path();
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    for (index, path) in job.collect_files()? {
+        writeln!(out, "{}", job.shown_path(index, &path))?;
+    }
+    Ok(())
+}
 
-        if path.is_dir() { continue; }
+fn run_check(args: &GenerateArgs) -> Result<()> {
+    let report = check::check(&args.paths, &args.options())?;
+    if report.up_to_date {
+        status!("{} 与源码一致", report.output.display());
+        return Ok(());
+    }
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    for path in &report.changed {
+        writeln!(out, "M {}", path)?;
+    }
+    for path in &report.added {
+        writeln!(out, "A {}", path)?;
+    }
+    for path in &report.removed {
+        writeln!(out, "D {}", path)?;
+    }
+    if report.changed.is_empty() && report.added.is_empty() && report.removed.is_empty() {
+        eprintln!("各文件内容相同，但文档的其余部分（目录树、目录、选项等）不同");
+    }
+    Err(Error::OutOfDate(report.output))
+}
 
-        if path.file_name() == Some(out_file_name_os) { continue; }
-        if let Ok(abs) = path.canonicalize() {
-             if abs == out_file_abs { continue; }
+fn run_verify(args: &VerifyArgs) -> Result<()> {
+    let report = manifest::verify(&args.document, args.manifest.as_deref())?;
+    if report.mismatched.is_empty() {
+        status!("已按 {} 校验 {} 个文件", report.manifest.display(), report.checked);
+        if report.size_changed {
+            status!("各文件内容一致，但文档的大小与清单不同，代码块之外的部分有改动");
         }
+        return Ok(());
+    }
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    for path in &report.mismatched {
+        writeln!(out, "{}", path)?;
+    }
+    Err(Error::HashMismatch(report.mismatched.len()))
+}
 
-        if let Some(ext) = path.extension() {
-            let ext_str = format!(".{}", ext.to_str().unwrap_or("").to_lowercase());
-            if get_ignore_extensions().contains(ext_str.as_str()) { continue; }
-        }
+fn run_explain(args: &ExplainArgs) -> Result<()> {
+    let steps = explain::explain(&args.paths, &args.options(), &args.file)?;
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    for step in &steps {
+        writeln!(out, "{} {}", if step.passed { "✓" } else { "✗" }, step.text)?;
+    }
+    if steps.iter().all(|s| s.passed) {
+        writeln!(out, "\n会被收录")?;
+    } else {
+        writeln!(out, "\n不会被收录")?;
+    }
+    Ok(())
+}
 
-        if let Ok(meta) = path.metadata() {
-            if meta.len() > 1024 * 1024 { continue; }
-        }
+fn run_extract(args: &ExtractArgs) -> Result<()> {
+    let mut pieces = Vec::new();
+    for input in &args.inputs {
+        pieces.extend(extract::parse(&fs::read_to_string(input)?));
+    }
+    let files = extract::assemble(pieces);
 
-        if !is_text_file(path) { continue; }
-
-        match fs::read(path) {
-            Ok(bytes) => {
-                let content = String::from_utf8_lossy(&bytes);
-                if content.trim().is_empty() { continue; }
-
-                let rel_path = path.strip_prefix(&source_path).unwrap_or(path);
-                let path_str = rel_path.display().to_string().replace("\\", "/");
-                
-                // 获取不带点的扩展名用于 Markdown 代码块标识
-                let file_ext = path.extension()
-                    .and_then(|s| s.to_str())
-                    .unwrap_or("")
-                    .to_lowercase();
-
-                // 修改：写入 Markdown 格式
-                writeln!(writer, "## File: {}\n", path_str)?;
-                writeln!(writer, "```{}", file_ext)?;
-                writeln!(writer, "{}", content)?;
-                writeln!(writer, "```\n")?;
-            }
-            Err(_) => continue,
-        }
+    let report = extract::write_files(&files, &args.output, args.force)?;
+    for path in &report.rejected {
+        status!("已拒绝不安全的路径：{}", path);
+    }
+    for path in &report.skipped_existing {
+        status!("已存在，跳过（使用 --force 覆盖）：{}", path);
     }
-    
-    writer.flush()?;
+    status!("已还原 {} 个文件到 {}", report.written, args.output.display());
 
+    // 文档带有 --hashes 的校验行时逐个核对；不符的文件仍会写出，便于查看
+    let mismatched: Vec<&str> = files.iter().filter(|f| f.verified() == Some(false)).map(|f| f.path.as_str()).collect();
+    for path in &mismatched {
+        eprintln!("SHA-256 不符（内容可能已损坏、被截断，或带有 --line-numbers 的行号）：{}", path);
+    }
+    if !mismatched.is_empty() {
+        return Err(Error::HashMismatch(mismatched.len()));
+    }
     Ok(())
 }
 
+fn run_app(mut command: Command) -> Result<()> {
+    if let Command::Generate(args) | Command::List(args) | Command::Check(args) | Command::Mcp(args) | Command::Serve(ServeArgs { generate: args, .. }) = &mut command {
+        args.read_files_from()?;
+    }
+    match command {
+        Command::Generate(args) if args.dry_run => run_dry_run(&args),
+        Command::Generate(args) if args.watch => run_watch(&args),
+        Command::Generate(args) => run_generate(&args),
+        Command::List(args) => run_list(&args),
+        Command::Check(args) => run_check(&args),
+        Command::Extract(args) => run_extract(&args),
+        Command::Explain(args) => run_explain(&args),
+        Command::Verify(args) => run_verify(&args),
+        Command::Serve(args) => run_serve(&args),
+        Command::Mcp(args) => Ok(mcp::run(&args.paths, &args.options())?),
+        Command::Completions { shell } => {
+            completions::generate(shell, Cli::command(), &mut io::stdout());
+            Ok(())
+        }
+        Command::Gui => Ok(gui::run()?),
+        Command::InstallShell => Ok(shell::install()?),
+        Command::UninstallShell => Ok(shell::uninstall()?),
+    }
+}
+
 fn main() {
-    if let Err(_) = run_app() {
-        std::process::exit(1);
+    let attached = console::attach();
+    // 从资源管理器双击启动（没有控制台也没有参数）时打开拖放窗口
+    if !attached && std::env::args_os().len() == 1 {
+        if let Err(e) = gui::run() {
+            console::log_error(&e.to_string());
+            std::process::exit(1);
+        }
+        return;
     }
-}
\ No newline at end of file
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        // 没有控制台时参数错误无处显示，改写到日志文件
+        Err(e) if !attached && e.use_stderr() => {
+            console::log_error(&e.to_string());
+            std::process::exit(e.exit_code());
+        }
+        Err(e) => e.exit(),
+    };
+    logging::init(cli.verbose, cli.quiet);
+    // 从资源管理器启动时没有控制台，--console 会新开一个窗口显示进度
+    let allocated = cli.console && !attached && console::alloc();
+
+    let result = run_app(cli.into_command());
+    if let Err(e) = &result {
+        if attached || allocated {
+            eprintln!("错误：{}", e);
+        } else {
+            console::log_error(&e.to_string());
+        }
+    }
+    if allocated {
+        console::pause();
+    }
+    if let Err(e) = result {
+        std::process::exit(e.exit_code());
+    }
+}
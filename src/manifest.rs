@@ -0,0 +1,150 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::render::FileEntry;
+use crate::sha256;
+use crate::{Error, Result};
+
+/// 清单的文件名为文档名加上这一后缀：project.md -> project.md.manifest.json
+pub const MANIFEST_SUFFIX: &str = ".manifest.json";
+
+/// 清单格式的版本
+const VERSION: u32 = 1;
+
+/// 文档旁的清单：收录的每个文件在文档中的位置与内容摘要，verify 据此校验文档
+#[derive(Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: u32,
+    /// 文档的文件名
+    pub document: String,
+    /// 文档的字节数
+    pub size: u64,
+    pub files: Vec<ManifestFile>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ManifestFile {
+    /// 文档中的显示路径
+    pub path: String,
+    /// 源文件的字节数
+    pub size: u64,
+    /// 文档中代码块内容的 SHA-256
+    pub sha256: String,
+    /// 代码块内容在文档中的字节偏移
+    pub offset: u64,
+    /// 代码块内容的字节数
+    pub length: u64,
+    /// --dedup 时内容与该文件相同，位置与摘要都取自该文件
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duplicate_of: Option<String>,
+}
+
+/// verify 的结果
+pub struct VerifyReport {
+    pub manifest: PathBuf,
+    /// 校验过的文件数
+    pub checked: usize,
+    /// 内容与清单中的摘要不符的文件
+    pub mismatched: Vec<String>,
+    /// 文档的大小与清单记录的不同：各文件内容一致时说明改动在代码块之外
+    pub size_changed: bool,
+}
+
+/// document 的清单位置
+pub fn manifest_path(document: &Path) -> PathBuf {
+    let mut name = document.file_name().unwrap_or_default().to_os_string();
+    name.push(MANIFEST_SUFFIX);
+    document.with_file_name(name)
+}
+
+/// 按写出文档时记录的各小节内容位置（与 entries 一一对应）生成清单，摘要从写好的文档中读取。
+/// 占位项没有内容，不在清单中。返回清单的路径
+pub fn write(document: &Path, entries: &[FileEntry], spans: &[Option<Range<u64>>]) -> io::Result<PathBuf> {
+    let mut file = File::open(document)?;
+    let mut located: HashMap<String, (Range<u64>, String)> = HashMap::new();
+    let mut files = Vec::with_capacity(entries.len());
+    for (entry, span) in entries.iter().zip(spans) {
+        let path = entry.display_path();
+        let (range, sha256, duplicate_of) = match (span, &entry.duplicate_of) {
+            (Some(range), _) => {
+                let sha256 = hash_range(&mut file, range)?;
+                located.insert(path.clone(), (range.clone(), sha256.clone()));
+                (range.clone(), sha256, None)
+            }
+            // 引用总在原文件之后
+            (None, Some(original)) => match located.get(original) {
+                Some((range, sha256)) => (range.clone(), sha256.clone(), Some(original.clone())),
+                None => continue,
+            },
+            (None, None) => continue,
+        };
+        files.push(ManifestFile {
+            path,
+            size: entry.size,
+            sha256,
+            offset: range.start,
+            length: range.end - range.start,
+            duplicate_of,
+        });
+    }
+
+    let manifest = Manifest {
+        version: VERSION,
+        document: document.file_name().unwrap_or_default().to_string_lossy().into_owned(),
+        size: file.metadata()?.len(),
+        files,
+    };
+    let path = manifest_path(document);
+    fs::write(&path, serde_json::to_vec_pretty(&manifest)?)?;
+    Ok(path)
+}
+
+/// 按清单逐个核对文档中各文件内容的摘要；manifest 为 None 时使用文档旁的默认清单
+pub fn verify(document: &Path, manifest: Option<&Path>) -> Result<VerifyReport> {
+    let manifest_file = manifest.map_or_else(|| manifest_path(document), Path::to_path_buf);
+    let bytes = fs::read(&manifest_file).map_err(not_found(&manifest_file))?;
+    let manifest: Manifest = serde_json::from_slice(&bytes)
+        .map_err(|e| Error::InvalidOptions(format!("无法解析清单 {}：{}", manifest_file.display(), e)))?;
+    if manifest.version != VERSION {
+        return Err(Error::InvalidOptions(format!("不支持的清单版本 {}", manifest.version)));
+    }
+
+    let mut file = File::open(document).map_err(not_found(document))?;
+    let size = file.metadata()?.len();
+    let mut mismatched = Vec::new();
+    for entry in &manifest.files {
+        let range = entry.offset..entry.offset + entry.length;
+        // 文档被截短时超出的部分读不到，摘要自然不符
+        if hash_range(&mut file, &range)? != entry.sha256 {
+            mismatched.push(entry.path.clone());
+        }
+    }
+    Ok(VerifyReport { manifest: manifest_file, checked: manifest.files.len(), mismatched, size_changed: size != manifest.size })
+}
+
+fn not_found(path: &Path) -> impl FnOnce(io::Error) -> Error + '_ {
+    move |e| match e.kind() {
+        io::ErrorKind::NotFound => Error::PathNotFound(path.to_path_buf()),
+        _ => Error::Io(e),
+    }
+}
+
+/// 文件中一段字节的 SHA-256，逐块读取
+fn hash_range(file: &mut File, range: &Range<u64>) -> io::Result<String> {
+    file.seek(SeekFrom::Start(range.start))?;
+    let mut reader = file.take(range.end - range.start);
+    let mut hasher = Sha256::new();
+    let mut buffer = [0; 64 * 1024];
+    loop {
+        match reader.read(&mut buffer)? {
+            0 => break,
+            n => hasher.update(&buffer[..n]),
+        }
+    }
+    Ok(sha256::finish(hasher))
+}
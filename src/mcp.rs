@@ -0,0 +1,155 @@
+use serde_json::{json, Value};
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+
+use code2xml::job::Job;
+use code2xml::progress::Progress;
+use code2xml::tree::Tree;
+use code2xml::{ConversionOptions, Error, Format};
+
+/// 支持的协议版本，客户端请求其中之一时按它回复，否则回复最新的
+const PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+/// 以 MCP（Model Context Protocol）服务器运行：从 stdin 逐行读取 JSON-RPC 请求，向 stdout 写出响应。
+/// 每次调用工具都按启动时的路径与选项重新扫描，得到的总是当前的源码
+pub fn run(paths: &[PathBuf], options: &ConversionOptions) -> io::Result<()> {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    for line in stdin.lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(request) => handle(&request, paths, options),
+            Err(e) => Some(error(Value::Null, -32700, &format!("无法解析请求：{}", e))),
+        };
+        if let Some(response) = response {
+            let mut out = stdout.lock();
+            serde_json::to_writer(&mut out, &response)?;
+            writeln!(out)?;
+            out.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// 处理一条请求；通知（没有 id）不回复
+fn handle(request: &Value, paths: &[PathBuf], options: &ConversionOptions) -> Option<Value> {
+    let id = request.get("id")?.clone();
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+    let result = match request.get("method").and_then(Value::as_str).unwrap_or("") {
+        "initialize" => {
+            let requested = params.get("protocolVersion").and_then(Value::as_str);
+            let version = requested.filter(|v| PROTOCOL_VERSIONS.contains(v)).unwrap_or(PROTOCOL_VERSIONS[0]);
+            json!({
+                "protocolVersion": version,
+                "capabilities": { "tools": {} },
+                "serverInfo": { "name": "code2md", "version": env!("CARGO_PKG_VERSION") },
+            })
+        }
+        "ping" => json!({}),
+        "tools/list" => json!({ "tools": tools() }),
+        "tools/call" => {
+            let name = params.get("name").and_then(Value::as_str).unwrap_or("");
+            let arguments = params.get("arguments").cloned().unwrap_or(Value::Null);
+            // 工具执行失败按 MCP 的约定放在结果里，让模型看到原因
+            match call(name, &arguments, paths, options) {
+                Ok(text) => json!({ "content": [{ "type": "text", "text": text }] }),
+                Err(message) => json!({ "content": [{ "type": "text", "text": message }], "isError": true }),
+            }
+        }
+        method => return Some(error(id, -32601, &format!("不支持的方法：{}", method))),
+    };
+    Some(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+}
+
+fn error(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+fn tools() -> Value {
+    json!([
+        {
+            "name": "get_project_markdown",
+            "description": "Return the whole project as one Markdown document: directory tree, table of contents and every included source file in a fenced code block.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "include": { "type": "array", "items": { "type": "string" }, "description": "Only include files matching these globs, e.g. \"src/**/*.rs\"." },
+                },
+            },
+        },
+        {
+            "name": "get_file",
+            "description": "Return the content of one project file, given its path as shown by get_tree (relative to the project root).",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "path": { "type": "string" } },
+                "required": ["path"],
+            },
+        },
+        {
+            "name": "get_tree",
+            "description": "Return the directory tree of the files that get_project_markdown would include.",
+            "inputSchema": { "type": "object", "properties": {} },
+        },
+    ])
+}
+
+fn call(name: &str, arguments: &Value, paths: &[PathBuf], options: &ConversionOptions) -> Result<String, String> {
+    // 文档只在内存中生成，不写文件、不分卷
+    let capture = ConversionOptions {
+        capture: true,
+        output: None,
+        save_inside: false,
+        stdout: false,
+        progress: false,
+        manifest: false,
+        split_files: false,
+        max_tokens: None,
+        max_output_size: None,
+        ..options.clone()
+    };
+    match name {
+        "get_project_markdown" => {
+            let mut options = ConversionOptions { format: Some(Format::Markdown), ..capture };
+            if let Some(globs) = arguments.get("include").and_then(Value::as_array) {
+                options.include.extend(globs.iter().filter_map(Value::as_str).map(str::to_string));
+            }
+            let report = code2xml::convert_all(paths, &options).map_err(|e| e.to_string())?;
+            Ok(report.document.unwrap_or_default())
+        }
+        "get_file" => {
+            let path = arguments.get("path").and_then(Value::as_str).ok_or("缺少参数 path")?;
+            let path = path.trim_start_matches("./");
+            let options = ConversionOptions {
+                format: Some(Format::Json),
+                selection: Some(vec![path.to_string()]),
+                preamble: None,
+                stats_only: false,
+                dedup: false,
+                ..capture
+            };
+            let missing = || format!("{} 不存在或未被收录（可用 get_tree 查看收录的文件）", path);
+            let report = match code2xml::convert_all(paths, &options) {
+                Ok(report) => report,
+                Err(Error::NoFiles) => return Err(missing()),
+                Err(e) => return Err(e.to_string()),
+            };
+            let files: Vec<Value> = serde_json::from_str(&report.document.unwrap_or_default()).map_err(|e| e.to_string())?;
+            let file = files.first().ok_or_else(missing)?;
+            if let Some(reason) = file.get("skipped").and_then(Value::as_str) {
+                return Err(format!("{}：{}", path, reason));
+            }
+            Ok(file.get("content").and_then(Value::as_str).unwrap_or("").to_string())
+        }
+        "get_tree" => {
+            let entries = code2xml::scan(paths, &capture).map_err(|e| e.to_string())?;
+            let included: Vec<&str> = entries.iter().filter(|e| e.included()).map(|e| e.display.as_str()).collect();
+            let job = Job::new(paths, &capture, Progress::hidden()).map_err(|e| e.to_string())?;
+            Ok(Tree::from_paths(included).render(&job.project_name()))
+        }
+        _ => Err(format!("没有名为 {} 的工具", name)),
+    }
+}
@@ -0,0 +1,268 @@
+use rayon::prelude::*;
+use std::sync::OnceLock;
+use tree_sitter::{Language, Parser, Query, QueryCursor, StreamingIterator};
+
+use crate::render::FileEntry;
+
+/// 文件中的一个顶层定义
+#[derive(Clone, Debug)]
+pub struct Symbol {
+    pub name: String,
+    /// 定义的种类，即查询中捕获的名称，如 function、struct
+    pub kind: &'static str,
+}
+
+/// 一种语言的 tree-sitter 语法，以及找出各定义名称的查询
+struct Grammar {
+    extensions: &'static [&'static str],
+    language: Language,
+    query: Query,
+}
+
+const RUST: &str = r#"
+(function_item name: (identifier) @function)
+(function_signature_item name: (identifier) @function)
+(struct_item name: (type_identifier) @struct)
+(enum_item name: (type_identifier) @enum)
+(union_item name: (type_identifier) @union)
+(trait_item name: (type_identifier) @trait)
+(type_item name: (type_identifier) @type)
+(impl_item type: (_) @impl)
+(mod_item name: (identifier) @module)
+(macro_definition name: (identifier) @macro)
+"#;
+
+const PYTHON: &str = r#"
+(function_definition name: (identifier) @function)
+(class_definition name: (identifier) @class)
+"#;
+
+const JAVASCRIPT: &str = r#"
+(function_declaration name: (identifier) @function)
+(generator_function_declaration name: (identifier) @function)
+(class_declaration name: (identifier) @class)
+(method_definition name: (property_identifier) @function)
+(variable_declarator name: (identifier) @function value: [(arrow_function) (function_expression)])
+"#;
+
+/// TypeScript 与 TSX 共用；类名是 type_identifier，另有接口、类型别名、枚举与命名空间
+const TYPESCRIPT: &str = r#"
+(function_declaration name: (identifier) @function)
+(function_signature name: (identifier) @function)
+(generator_function_declaration name: (identifier) @function)
+(class_declaration name: (type_identifier) @class)
+(abstract_class_declaration name: (type_identifier) @class)
+(method_definition name: (property_identifier) @function)
+(abstract_method_signature name: (property_identifier) @function)
+(variable_declarator name: (identifier) @function value: [(arrow_function) (function_expression)])
+(interface_declaration name: (type_identifier) @interface)
+(type_alias_declaration name: (type_identifier) @type)
+(enum_declaration name: (identifier) @enum)
+(internal_module name: (_) @namespace)
+(module name: (_) @namespace)
+"#;
+
+const GO: &str = r#"
+(function_declaration name: (identifier) @function)
+(method_declaration name: (field_identifier) @function)
+(type_spec name: (type_identifier) @struct type: (struct_type))
+(type_spec name: (type_identifier) @interface type: (interface_type))
+(type_spec name: (type_identifier) @type)
+"#;
+
+const JAVA: &str = r#"
+(class_declaration name: (identifier) @class)
+(record_declaration name: (identifier) @class)
+(interface_declaration name: (identifier) @interface)
+(annotation_type_declaration name: (identifier) @interface)
+(enum_declaration name: (identifier) @enum)
+(method_declaration name: (identifier) @function)
+(constructor_declaration name: (identifier) @function)
+"#;
+
+/// C 与 C++ 共用的部分：函数定义与头文件中的声明、带定义体的结构体等、typedef 与宏
+const C: &str = r#"
+(function_definition declarator: (function_declarator declarator: (_) @function))
+(function_definition declarator: (pointer_declarator declarator: (function_declarator declarator: (_) @function)))
+(declaration declarator: (function_declarator declarator: (_) @function))
+(declaration declarator: (pointer_declarator declarator: (function_declarator declarator: (_) @function)))
+(struct_specifier name: (type_identifier) @struct body: (_))
+(union_specifier name: (type_identifier) @union body: (_))
+(enum_specifier name: (type_identifier) @enum body: (_))
+(type_definition declarator: (type_identifier) @type)
+(preproc_function_def name: (identifier) @macro)
+"#;
+
+const CPP: &str = r#"
+(class_specifier name: (type_identifier) @class body: (_))
+(namespace_definition name: (_) @namespace)
+(alias_declaration name: (type_identifier) @type)
+(field_declaration declarator: (function_declarator declarator: (_) @function))
+"#;
+
+fn grammars() -> &'static [Grammar] {
+    static GRAMMARS: OnceLock<Vec<Grammar>> = OnceLock::new();
+    GRAMMARS.get_or_init(|| {
+        let grammar = |extensions, language: Language, query: &str| Grammar {
+            extensions,
+            query: Query::new(&language, query).expect("内置的大纲查询有误"),
+            language,
+        };
+        vec![
+            grammar(&["rs"], tree_sitter_rust::LANGUAGE.into(), RUST),
+            grammar(&["py", "pyi", "pyw"], tree_sitter_python::LANGUAGE.into(), PYTHON),
+            grammar(&["js", "mjs", "cjs", "jsx"], tree_sitter_javascript::LANGUAGE.into(), JAVASCRIPT),
+            grammar(&["ts", "mts", "cts"], tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(), TYPESCRIPT),
+            grammar(&["tsx"], tree_sitter_typescript::LANGUAGE_TSX.into(), TYPESCRIPT),
+            grammar(&["go"], tree_sitter_go::LANGUAGE.into(), GO),
+            grammar(&["java"], tree_sitter_java::LANGUAGE.into(), JAVA),
+            grammar(&["c"], tree_sitter_c::LANGUAGE.into(), C),
+            // .h 多半也可能是 C++ 头文件，C++ 的语法能解析其中的类
+            grammar(
+                &["cc", "cpp", "cxx", "c++", "h", "hh", "hpp", "hxx", "h++"],
+                tree_sitter_cpp::LANGUAGE.into(),
+                &format!("{}{}", C, CPP),
+            ),
+        ]
+    })
+}
+
+/// 只保留定义了函数、类、结构体等的行（保留缩进），得到代码的大纲。
+/// 依据 tree-sitter 的语法树判断，签名跨行时只保留名称所在的行；没有对应语法的文件返回 None
+pub fn outline(content: &str, extension: &str) -> Option<String> {
+    let definitions = definitions(content, extension)?;
+    let lines: Vec<&str> = content.lines().collect();
+    let mut out = String::new();
+    let mut last = None;
+    for (row, _, _) in definitions {
+        let Some(line) = lines.get(row).filter(|_| last != Some(row)) else { continue };
+        last = Some(row);
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    Some(out)
+}
+
+/// 顶层（不缩进的行上）定义的名称，按出现顺序；没有对应语法的文件为空
+pub fn symbols(content: &str, extension: &str) -> Vec<Symbol> {
+    let lines: Vec<&str> = content.lines().collect();
+    definitions(content, extension)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(row, _, _)| lines.get(*row).is_some_and(|line| !line.starts_with(char::is_whitespace)))
+        .map(|(_, name, kind)| Symbol { name: name.to_string(), kind })
+        .collect()
+}
+
+/// 为收录内容的文件提取顶层定义；重复文件只在原文件处列出
+pub fn index_entries(entries: &mut [FileEntry]) {
+    entries.par_iter_mut().filter(|e| e.is_included() && e.duplicate_of.is_none()).for_each(|entry| {
+        entry.symbols = symbols(&entry.content, &entry.extension);
+    });
+}
+
+/// 解析文件，按出现顺序给出各定义名称所在的行号、名称与种类；没有对应语法时为 None
+fn definitions<'a>(content: &'a str, extension: &str) -> Option<Vec<(usize, &'a str, &'static str)>> {
+    let grammar = grammar_for(content, extension)?;
+    let mut parser = Parser::new();
+    parser.set_language(&grammar.language).ok()?;
+    let tree = parser.parse(content, None)?;
+
+    // 同一名称可能被多个模式匹配（Go 的 struct 与 type），取排在前面的模式
+    let mut found = Vec::new();
+    let mut cursor = QueryCursor::new();
+    let mut matches = cursor.matches(&grammar.query, tree.root_node(), content.as_bytes());
+    while let Some(m) = matches.next() {
+        for capture in m.captures {
+            let kind = grammar.query.capture_names()[capture.index as usize];
+            found.push((capture.node.start_byte(), m.pattern_index, capture.node, kind));
+        }
+    }
+    found.sort_by_key(|&(start, pattern, _, _)| (start, pattern));
+    found.dedup_by_key(|&mut (start, _, _, _)| start);
+
+    Some(
+        found
+            .into_iter()
+            .filter_map(|(_, _, node, kind)| {
+                let name = node.utf8_text(content.as_bytes()).ok()?;
+                Some((node.start_position().row, name, kind))
+            })
+            .collect(),
+    )
+}
+
+/// 按扩展名找语法，没有扩展名时看 shebang
+fn grammar_for(content: &str, extension: &str) -> Option<&'static Grammar> {
+    let extension = match extension {
+        "" => {
+            let first = content.lines().next().filter(|l| l.starts_with("#!"))?;
+            if first.contains("python") {
+                "py"
+            } else if first.contains("node") {
+                "js"
+            } else {
+                return None;
+            }
+        }
+        other => other,
+    };
+    grammars().iter().find(|g| g.extensions.contains(&extension))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(content: &str, extension: &str) -> Vec<(String, &'static str)> {
+        symbols(content, extension).into_iter().map(|s| (s.name, s.kind)).collect()
+    }
+
+    #[test]
+    fn queries_compile() {
+        assert_eq!(grammars().len(), 9);
+    }
+
+    #[test]
+    fn rust_outline_keeps_indented_methods() {
+        let code = "pub struct Foo {\n    a: u32,\n}\n\nimpl Foo {\n    pub fn new() -> Foo {\n        Foo { a: 1 }\n    }\n}\n";
+        assert_eq!(outline(code, "rs").unwrap(), "pub struct Foo {\nimpl Foo {\n    pub fn new() -> Foo {\n");
+        assert_eq!(names(code, "rs"), [("Foo".to_string(), "struct"), ("Foo".to_string(), "impl")]);
+    }
+
+    #[test]
+    fn typescript_declarations() {
+        let code = "export interface Props {\n  id: string;\n}\ntype Id = string;\nenum Color { Red }\nexport class View {\n  render(): void {}\n}\n";
+        assert_eq!(
+            names(code, "ts"),
+            [
+                ("Props".to_string(), "interface"),
+                ("Id".to_string(), "type"),
+                ("Color".to_string(), "enum"),
+                ("View".to_string(), "class"),
+            ]
+        );
+        assert!(outline(code, "ts").unwrap().contains("  render(): void {}\n"));
+    }
+
+    #[test]
+    fn go_types_by_shape() {
+        let code = "package main\n\ntype A struct{}\ntype B interface{}\ntype C int\n\nfunc (a A) Run() {}\n";
+        assert_eq!(
+            names(code, "go"),
+            [
+                ("A".to_string(), "struct"),
+                ("B".to_string(), "interface"),
+                ("C".to_string(), "type"),
+                ("Run".to_string(), "function"),
+            ]
+        );
+    }
+
+    #[test]
+    fn python_shebang_and_unknown_language() {
+        let code = "#!/usr/bin/env python3\nclass A:\n    def f(self):\n        pass\n";
+        assert_eq!(outline(code, "").unwrap(), "class A:\n    def f(self):\n");
+        assert!(outline("whatever", "txt").is_none());
+    }
+}
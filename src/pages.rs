@@ -0,0 +1,55 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::render::{self, FileEntry, RenderOptions};
+
+/// 每个文件一页时的索引页
+pub const INDEX_FILE_NAME: &str = "index.md";
+
+/// 页面相对于输出目录的路径：`src/main.rs` -> `src/main.rs.md`，多根目录时带根目录前缀
+fn page_path(group: &str, label: &str) -> String {
+    if group.is_empty() {
+        format!("{}.md", label)
+    } else {
+        format!("{}/{}.md", group, label)
+    }
+}
+
+/// 每个文件写成 dir 下与源码结构相同的一页，另写一个链接到各页的 index.md。
+/// 返回写出的全部文件，索引在最前
+pub fn write_pages(dir: &Path, entries: &[FileEntry], opts: &RenderOptions) -> io::Result<Vec<PathBuf>> {
+    let sections = render::sections(entries, opts);
+    let mut written = Vec::with_capacity(sections.len() + 1);
+    written.push(dir.join(INDEX_FILE_NAME));
+
+    let mut links = String::new();
+    for section in &sections {
+        let page = page_path(&section.group, &section.label);
+        let path = dir.join(&page);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let up = "../".repeat(page.matches('/').count());
+        let mut writer = BufWriter::new(File::create(&path)?);
+        write!(writer, "> [Index]({}{})\n\n{}", up, INDEX_FILE_NAME, section.render())?;
+        writer.flush()?;
+        written.push(path);
+
+        let label = if section.group.is_empty() { section.label.clone() } else { format!("{}/{}", section.group, section.label) };
+        links.push_str(&format!("- [{}]({})\n", label.replace('[', "\\[").replace(']', "\\]"), page.replace(' ', "%20")));
+    }
+
+    let mut writer = BufWriter::new(File::create(dir.join(INDEX_FILE_NAME))?);
+    if let Some(front_matter) = opts.front_matter {
+        write!(writer, "{}", front_matter)?;
+    }
+    writeln!(writer, "# {}\n", opts.title)?;
+    write!(writer, "{}", render::render_front(entries, opts))?;
+    writeln!(writer, "{} {}\n\n{}", "#".repeat(opts.heading_level), opts.locale.strings().files, links)?;
+    if let Some(skipped) = opts.skipped_files {
+        write!(writer, "{}", render::render_skipped(skipped, opts))?;
+    }
+    writer.flush()?;
+    Ok(written)
+}
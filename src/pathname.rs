@@ -0,0 +1,124 @@
+use std::borrow::Cow;
+use std::ffi::OsStr;
+use std::path::{Component, Path};
+
+/// 路径中有转义部分的文件，标题末尾加上这一标记
+pub const ESCAPED_MARKER: &str = " (escaped path)";
+
+/// 以 / 分隔的显示路径。名称不是合法 UTF-8、含控制字符或双向控制字符、含反斜杠的部分改写为转义形式：
+/// 无效字节为 `\xFF`（Windows 上落单的代理项为 `\u{D800}`），控制字符为 `\u{7}`，反斜杠为 `\\`
+pub fn display(path: &Path) -> String {
+    path.components()
+        .map(|c| match c {
+            Component::Normal(name) => escape(name),
+            Component::RootDir => String::new(),
+            other => other.as_os_str().to_string_lossy().into_owned(),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// 路径中是否有需要转义的名称，即 display 的结果与原名不同
+pub fn is_escaped(path: &Path) -> bool {
+    path.components().any(|c| matches!(c, Component::Normal(name) if needs_escape(name)))
+}
+
+fn needs_escape(name: &OsStr) -> bool {
+    name.to_str().is_none_or(|s| s.chars().any(|c| c == '\\' || is_unusual(c)))
+}
+
+/// 控制字符与可改变显示顺序的双向控制字符
+fn is_unusual(c: char) -> bool {
+    c.is_control() || matches!(c, '\u{200E}' | '\u{200F}' | '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}')
+}
+
+fn escape(name: &OsStr) -> String {
+    if !needs_escape(name) {
+        return name.to_string_lossy().into_owned();
+    }
+    let mut out = String::new();
+    for chunk in decode(name) {
+        match chunk {
+            Ok('\\') => out.push_str("\\\\"),
+            Ok(c) if is_unusual(c) => out.push_str(&format!("\\u{{{:X}}}", c as u32)),
+            Ok(c) => out.push(c),
+            Err(invalid) => out.push_str(&invalid),
+        }
+    }
+    out
+}
+
+/// 逐字符解码，无法解码的部分给出其转义形式
+#[cfg(unix)]
+fn decode(name: &OsStr) -> Vec<Result<char, String>> {
+    use std::os::unix::ffi::OsStrExt;
+    let mut out = Vec::new();
+    for chunk in name.as_bytes().utf8_chunks() {
+        out.extend(chunk.valid().chars().map(Ok));
+        out.extend(chunk.invalid().iter().map(|b| Err(format!("\\x{:02X}", b))));
+    }
+    out
+}
+
+#[cfg(windows)]
+fn decode(name: &OsStr) -> Vec<Result<char, String>> {
+    use std::os::windows::ffi::OsStrExt;
+    char::decode_utf16(name.encode_wide())
+        .map(|r| r.map_err(|e| format!("\\u{{{:X}}}", e.unpaired_surrogate())))
+        .collect()
+}
+
+#[cfg(not(any(unix, windows)))]
+fn decode(name: &OsStr) -> Vec<Result<char, String>> {
+    name.to_string_lossy().chars().map(Ok).collect()
+}
+
+/// 打开文件时使用的路径。Windows 上超过 MAX_PATH（260 个字符）的路径改写为 `\\?\` 开头的扩展长度形式，
+/// 否则深层目录中的文件无法打开；已是扩展长度形式或较短的路径原样返回
+#[cfg(windows)]
+pub fn extended(path: &Path) -> Cow<'_, Path> {
+    use std::ffi::OsString;
+    use std::path::{PathBuf, Prefix};
+    // 含结尾的空字符
+    const MAX_PATH: usize = 260;
+    if path.as_os_str().len() < MAX_PATH - 1 {
+        return Cow::Borrowed(path);
+    }
+    let Ok(absolute) = std::path::absolute(path) else {
+        return Cow::Borrowed(path);
+    };
+    let mut components = absolute.components();
+    let mut out = match components.next() {
+        Some(Component::Prefix(prefix)) => match prefix.kind() {
+            Prefix::Disk(drive) => OsString::from(format!(r"\\?\{}:", drive as char)),
+            Prefix::UNC(server, share) => {
+                let mut out = OsString::from(r"\\?\UNC\");
+                out.push(server);
+                out.push(r"\");
+                out.push(share);
+                out
+            }
+            _ => return Cow::Borrowed(path),
+        },
+        _ => return Cow::Borrowed(path),
+    };
+    // 扩展长度路径不再解析 . 与 ..，须事先去掉
+    let mut names: Vec<&OsStr> = Vec::new();
+    for component in components {
+        match component {
+            Component::Normal(name) => names.push(name),
+            Component::ParentDir => { names.pop(); }
+            _ => {}
+        }
+    }
+    for name in names {
+        out.push(r"\");
+        out.push(name);
+    }
+    Cow::Owned(PathBuf::from(out))
+}
+
+#[cfg(not(windows))]
+pub fn extended(path: &Path) -> Cow<'_, Path> {
+    Cow::Borrowed(path)
+}
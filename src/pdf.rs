@@ -0,0 +1,54 @@
+//! 从 PDF 中提取纯文本（--extract-pdf）。解析与文字还原交给 lopdf，
+//! 不处理加密文档与扫描件（图片中的文字）
+
+use lopdf::Document;
+use std::thread;
+
+/// 解析所在线程的栈大小；lopdf 递归解析嵌套的数组与字典，默认的栈对构造出的深层嵌套不够用
+const STACK_SIZE: usize = 64 * 1024 * 1024;
+
+/// 把 PDF 中的文字按页提取出来，页与页之间空一行
+pub fn extract_text(bytes: &[u8]) -> Result<String, String> {
+    thread::scope(|scope| {
+        thread::Builder::new()
+            .stack_size(STACK_SIZE)
+            .spawn_scoped(scope, || extract(bytes))
+            .map_err(|e| e.to_string())?
+            .join()
+            .unwrap_or_else(|_| Err("解析时出错".to_string()))
+    })
+}
+
+fn extract(bytes: &[u8]) -> Result<String, String> {
+    let document = Document::load_mem(bytes).map_err(|e| e.to_string())?;
+    if document.is_encrypted() {
+        return Err("PDF 已加密".to_string());
+    }
+    let mut out = String::new();
+    for &page in document.get_pages().keys() {
+        out.push_str(&document.extract_text(&[page]).map_err(|e| e.to_string())?);
+        out.push_str("\n\n");
+    }
+    Ok(tidy(&out))
+}
+
+/// 去掉行尾空白，多个空行合并为一个
+fn tidy(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut blank = 0;
+    for line in text.lines().map(str::trim_end) {
+        if line.is_empty() {
+            blank += 1;
+            continue;
+        }
+        if !out.is_empty() {
+            out.push_str(if blank > 0 { "\n\n" } else { "\n" });
+        }
+        blank = 0;
+        out.push_str(line);
+    }
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    out
+}
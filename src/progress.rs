@@ -0,0 +1,86 @@
+use indicatif::{HumanBytes, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+/// 终端进度条：扫描数、收录数、已读取字节与耗时。stderr 不是终端时自动隐藏
+pub struct Progress {
+    bar: ProgressBar,
+    included: AtomicUsize,
+    bytes: AtomicU64,
+}
+
+impl Progress {
+    pub fn new(enabled: bool) -> Progress {
+        let target = if enabled { ProgressDrawTarget::stderr() } else { ProgressDrawTarget::hidden() };
+        let bar = ProgressBar::with_draw_target(None, target);
+        bar.set_style(ProgressStyle::with_template("{spinner} [{elapsed_precise}] 扫描中… {pos} 个文件").unwrap());
+        bar.enable_steady_tick(std::time::Duration::from_millis(120));
+        Progress { bar, included: AtomicUsize::new(0), bytes: AtomicU64::new(0) }
+    }
+
+    pub fn hidden() -> Progress {
+        Progress::new(false)
+    }
+
+    pub fn scanned(&self) {
+        self.bar.inc(1);
+    }
+
+    /// 扫描结束，进入逐个文件检查与读取阶段
+    pub fn start_reading(&self, total: usize) {
+        self.bar.set_style(
+            ProgressStyle::with_template("{spinner} [{elapsed_precise}] {bar:30} {pos}/{len} {msg}")
+                .unwrap()
+                .progress_chars("=> "),
+        );
+        self.bar.set_position(0);
+        self.bar.set_length(total as u64);
+    }
+
+    pub fn processed(&self) {
+        self.bar.inc(1);
+    }
+
+    pub fn included(&self, bytes: u64) {
+        let files = self.included.fetch_add(1, Ordering::Relaxed) + 1;
+        let total = self.bytes.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        self.bar.set_message(format!("已收录 {} 个文件，{}", files, HumanBytes(total)));
+    }
+
+    pub fn finish(&self, written: u64) {
+        let files = self.included.load(Ordering::Relaxed);
+        self.bar.finish_and_clear();
+        if !self.bar.is_hidden() {
+            eprintln!(
+                "完成：收录 {} 个文件，写出 {}，耗时 {:.1?}",
+                files,
+                HumanBytes(written),
+                self.bar.elapsed()
+            );
+        }
+    }
+}
+
+/// 统计写入字节数的包装
+pub struct CountingWriter<W> {
+    inner: W,
+    pub count: u64,
+}
+
+impl<W: Write> CountingWriter<W> {
+    pub fn new(inner: W) -> Self {
+        CountingWriter { inner, count: 0 }
+    }
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.count += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,737 @@
+use indicatif::HumanBytes;
+use std::io::{self, Write};
+use std::ops::Range;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::anchor::Slugger;
+use crate::dialect::{self, Dialect};
+use crate::locale::Locale;
+use crate::outline::Symbol;
+use crate::progress::CountingWriter;
+use crate::pathname::ESCAPED_MARKER;
+use crate::stats::{Skip, SkipCounts};
+use crate::stream::Stream;
+use crate::tree::Tree;
+
+/// 一个将被写入文档的源文件
+pub struct FileEntry {
+    /// 所属根目录的显示名称，只有一个根目录时为空
+    pub root: String,
+    /// 相对于所属根目录、以 / 分隔的路径
+    pub rel_path: String,
+    /// rel_path 中有名称被转义（不是合法 UTF-8 或含控制字符），标题中注明
+    pub escaped_path: bool,
+    /// 不带点的小写扩展名
+    pub extension: String,
+    /// 代码块的语言标识，如 rust、python
+    pub language: String,
+    /// 源文件的字节数
+    pub size: u64,
+    /// 最后修改时间，取不到时为 None
+    pub modified: Option<SystemTime>,
+    pub content: String,
+    /// 仅在启用 token 统计时计算
+    pub tokens: Option<usize>,
+    /// 与文档中更早的某个文件内容相同时为其显示路径，此时只输出引用（--dedup）
+    pub duplicate_of: Option<String>,
+    /// 二进制或过大而未收录内容时的说明，如 "Binary file, 4.20 MiB, skipped"，
+    /// 此时只输出标题与这一行（--list-skipped-inline）
+    pub placeholder: Option<String>,
+    /// 较大的文件不读入内存（content 为空），写文档时才从源文件流式读取
+    pub stream: Option<Stream>,
+    /// 写入文档的内容（不含行号）的 SHA-256，仅在 --hashes 时计算
+    pub sha256: Option<String>,
+    /// 顶层定义，仅在 --symbols 时提取
+    pub symbols: Vec<Symbol>,
+    /// 内容是从 PDF 中提取的文字（--extract-pdf），不是文件本身
+    pub extracted: bool,
+}
+
+impl FileEntry {
+    /// 带根目录前缀的完整显示路径
+    pub fn display_path(&self) -> String {
+        if self.root.is_empty() {
+            self.rel_path.clone()
+        } else {
+            format!("{}/{}", self.root, self.rel_path)
+        }
+    }
+
+    /// 标题中使用的显示路径，有转义的名称时带上标记
+    pub fn marked_path(&self) -> String {
+        let path = self.display_path();
+        if self.escaped_path { path + ESCAPED_MARKER } else { path }
+    }
+
+    /// 内容的行数
+    pub fn line_count(&self) -> usize {
+        self.stream.as_ref().map_or_else(|| self.content.lines().count(), |s| s.lines)
+    }
+
+    /// 内容实际写入了文档（不是占位项）
+    pub fn is_included(&self) -> bool {
+        self.placeholder.is_none()
+    }
+}
+
+pub struct RenderOptions<'a> {
+    /// 目录树的根
+    pub project_name: &'a str,
+    /// HTML、AsciiDoc 与分页索引的标题
+    pub title: &'a str,
+    pub tree: bool,
+    /// 在目录树之后加入文件间引用关系的 Mermaid 图
+    pub import_graph: bool,
+    pub toc: bool,
+    /// 在目录之后加入符号索引
+    pub symbols: bool,
+    pub token_summary: bool,
+    pub line_numbers: bool,
+    pub metadata: bool,
+    /// Some 时在开头输出项目摘要，附带各原因跳过的文件数
+    pub summary: Option<&'a SkipCounts>,
+    /// Some 时在文档末尾列出每个被跳过的文件及原因
+    pub skipped_files: Option<&'a SkipCounts>,
+    /// 为每个目录输出二级标题，文件标题降为三级
+    pub group_by_dir: bool,
+    /// 代码块包在 <details> 中默认折叠
+    pub collapsible: bool,
+    /// 锚点、内部链接与折叠代码块的写法
+    pub dialect: Dialect,
+    /// 标题、表头等固定文字的语言
+    pub locale: Locale,
+    /// 写在文档最开头的 YAML front matter（含首尾的 ---）
+    pub front_matter: Option<&'a str>,
+    /// 放在文档最前面（front matter 之后）的说明文字，已去掉末尾空行
+    pub preamble: Option<&'a str>,
+    /// 文件标题与目录树、目录等文档级标题的级别；多根目录的分组标题高一级
+    pub heading_level: usize,
+    /// 文件标题的文本，{path} 处替换为路径
+    pub heading_template: &'a str,
+}
+
+impl RenderOptions<'_> {
+    /// 目录树、目录等文档级标题的 # 前缀
+    fn hashes(&self) -> String {
+        "#".repeat(self.heading_level)
+    }
+
+    /// 文件标题的级别；按目录分组时比目录标题低一级
+    fn file_level(&self) -> usize {
+        (self.heading_level + usize::from(self.group_by_dir)).min(6)
+    }
+}
+
+/// 文档中的一个文件小节；超大文件拆分后会对应多个小节
+pub struct Section {
+    /// 所属根目录，多根目录时按它分组输出一级标题
+    pub group: String,
+    /// 显示在标题与目录中的文本，通常就是相对路径
+    pub label: String,
+    /// 所在目录（相对于根目录，根目录下的文件为空），--group-by-dir 时按它输出目录标题
+    pub dir: String,
+    /// 文件标题的级别
+    pub level: usize,
+    /// 文件标题的模板，见 RenderOptions::heading_template
+    pub template: String,
+    /// 代码块包在 <details> 中，摘要行为路径
+    pub collapsible: bool,
+    /// 内容写成引用块而不是代码块，extract 不会还原；用于从 PDF 中提取的文字
+    pub quoted: bool,
+    /// 折叠与链接的写法，见 RenderOptions::dialect
+    pub dialect: Dialect,
+    /// 重复文件引用行的语言，见 RenderOptions::locale
+    pub locale: Locale,
+    /// 路径中有转义的名称，标题末尾注明
+    pub escaped: bool,
+    pub language: String,
+    /// 标题下的文件信息行，仅在 --metadata 时存在
+    pub meta: Option<String>,
+    pub content: String,
+    /// 内容与该文件相同，只输出引用而不输出代码块
+    pub duplicate_of: Option<String>,
+    /// 未收录内容的说明，只输出这一行而不输出代码块
+    pub placeholder: Option<String>,
+    /// 内容在写出时才从源文件流式读取，此时 content 为空
+    pub stream: Option<Stream>,
+    /// 内容的 SHA-256，仅在 --hashes 时存在
+    pub sha256: Option<String>,
+    /// 符号索引中指向本小节的顶层定义；文件拆成多段时只在第一段
+    pub symbols: Vec<Symbol>,
+}
+
+impl Section {
+    pub fn heading(&self) -> String {
+        let heading = self.template.replace("{path}", &self.label);
+        if self.escaped { heading + ESCAPED_MARKER } else { heading }
+    }
+
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        // 写入 Vec 不会失败；流式读取源文件失败时与读入内存时一样只是没有内容
+        let _ = self.write_to(&mut buffer);
+        String::from_utf8_lossy(&buffer).into_owned()
+    }
+
+    /// 写出小节，返回代码块内容相对于小节开头的字节范围（引用与占位项没有代码块，为 None）。
+    /// 流式读取的内容直接从源文件复制到 writer，不经过内存中的整段文本
+    pub fn write_to(&self, writer: &mut impl Write) -> io::Result<Option<Range<u64>>> {
+        // 修改：写入 Markdown 格式
+        let hashes = "#".repeat(self.level);
+        let meta = self.notes();
+        if let Some(original) = &self.duplicate_of {
+            write!(writer, "{} {}\n\n{}{}`{}`\n\n", hashes, self.heading(), meta, self.locale.strings().duplicate_prefix, original)?;
+            return Ok(None);
+        }
+        if let Some(note) = &self.placeholder {
+            write!(writer, "{} {}\n\n{}> {}\n\n", hashes, self.heading(), meta, note)?;
+            return Ok(None);
+        }
+        if self.quoted {
+            let body = dialect::quote(&format!("{}\n\n{}", self.locale.strings().extracted, self.content.trim_end()));
+            write!(writer, "{} {}\n\n{}{}\n\n", hashes, self.heading(), meta, body)?;
+            return Ok(None);
+        }
+        let fence = match &self.stream {
+            Some(stream) => fence(stream.longest_backticks),
+            None => fence_for(&self.content),
+        };
+        // Obsidian 不渲染 HTML 中的 Markdown，折叠时改用 callout，代码块逐行引用（此时不会流式读取）
+        if self.collapsible && self.dialect.quotes_collapsible() {
+            let head = format!("{} {}\n\n{}{} {}\n> {}{}\n", hashes, self.heading(), meta, CALLOUT_OPEN, self.label, fence, self.language);
+            writer.write_all(head.as_bytes())?;
+            // 连同结尾的空行与围栏一起引用，callout 中间不能断开
+            let body = dialect::quote(&format!("{}\n{}\n", self.content, fence));
+            writeln!(writer, "{}", body)?;
+            let start = head.len() as u64;
+            return Ok(Some(start..start + dialect::quote(&self.content).len() as u64));
+        }
+        let details = if self.collapsible {
+            format!("{}\n<summary>{}</summary>\n\n", DETAILS_OPEN, crate::format::html::escape(&self.label))
+        } else {
+            String::new()
+        };
+        let head = format!("{} {}\n\n{}{}{}{}\n", hashes, self.heading(), meta, details, fence, self.language);
+        writer.write_all(head.as_bytes())?;
+        let length = match &self.stream {
+            Some(stream) => {
+                let mut counter = CountingWriter::new(&mut *writer);
+                stream.write_to(&mut counter)?;
+                counter.count
+            }
+            None => {
+                writer.write_all(self.content.as_bytes())?;
+                self.content.len() as u64
+            }
+        };
+        write!(writer, "\n{}\n\n", fence)?;
+        if self.collapsible {
+            write!(writer, "</details>\n\n")?;
+        }
+        let start = head.len() as u64;
+        Ok(Some(start..start + length))
+    }
+
+    /// 标题下的引用行：文件信息与 SHA-256
+    fn notes(&self) -> String {
+        let mut notes = self.meta.as_deref().map(|m| format!("> {}\n\n", m)).unwrap_or_default();
+        if let Some(hash) = &self.sha256 {
+            notes.push_str(&format!("> {}{}\n\n", SHA256_PREFIX, hash));
+        }
+        notes
+    }
+
+    pub fn toc_line(&self, anchor: &str) -> String {
+        format!("- {}\n", self.dialect.link(&escape_link_text(&self.label), &self.heading(), anchor))
+    }
+
+    /// 符号索引中本小节的各行
+    pub fn symbol_rows(&self, anchor: &str) -> Vec<String> {
+        let label = escape_link_text(&self.label).replace('|', "\\|");
+        let heading = self.heading();
+        self.symbols
+            .iter()
+            .map(|s| format!("| `{}` | {} | {} |\n", s.name.replace('|', "\\|"), s.kind, self.dialect.link(&label, &heading, anchor)))
+            .collect()
+    }
+}
+
+/// 比内容中最长的连续反引号多一个的围栏（至少三个），内容里的 ``` 不会提前闭合代码块
+pub fn fence_for(content: &str) -> String {
+    let longest = content
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    fence(longest)
+}
+
+/// 比 longest 个连续反引号多一个的围栏，至少三个
+fn fence(longest: usize) -> String {
+    "`".repeat(longest.max(2) + 1)
+}
+
+/// 行号在拆分小节之前加上，超大文件切开后各段仍保留原始行号
+pub fn sections(entries: &[FileEntry], opts: &RenderOptions) -> Vec<Section> {
+    entries
+        .iter()
+        .map(|e| Section {
+            group: e.root.clone(),
+            label: e.rel_path.clone(),
+            dir: e.rel_path.rsplit_once('/').map_or("", |(dir, _)| dir).to_string(),
+            level: opts.file_level(),
+            template: opts.heading_template.to_string(),
+            collapsible: opts.collapsible,
+            quoted: e.extracted,
+            dialect: opts.dialect,
+            locale: opts.locale,
+            escaped: e.escaped_path,
+            language: e.language.clone(),
+            meta: (opts.metadata && e.is_included()).then(|| metadata_line(e)),
+            content: if e.duplicate_of.is_some() { String::new() } else { display_content(e, opts) },
+            duplicate_of: e.duplicate_of.clone(),
+            placeholder: e.placeholder.clone(),
+            stream: e.stream.clone().map(|s| s.with_line_numbers(opts.line_numbers)),
+            sha256: e.sha256.clone().filter(|_| e.duplicate_of.is_none()),
+            symbols: e.symbols.clone(),
+        })
+        .collect()
+}
+
+/// 文件在文档中实际显示的内容，按需加上行号
+pub fn display_content(entry: &FileEntry, opts: &RenderOptions) -> String {
+    if opts.line_numbers {
+        number_lines(&entry.content)
+    } else {
+        entry.content.clone()
+    }
+}
+
+/// `1.20 KiB · 42 lines · modified 2024-05-01 08:30 UTC · Rust`
+fn metadata_line(entry: &FileEntry) -> String {
+    let mut parts = vec![HumanBytes(entry.size).to_string()];
+    match entry.line_count() {
+        1 => parts.push("1 line".to_string()),
+        n => parts.push(format!("{} lines", n)),
+    }
+    if let Some(time) = entry.modified {
+        parts.push(format!("modified {}", format_utc(time)));
+    }
+    if let Some(language) = crate::lang::name(&entry.extension) {
+        parts.push(language.to_string());
+    }
+    parts.join(" · ")
+}
+
+/// `YYYY-MM-DD HH:MM UTC`
+fn format_utc(time: SystemTime) -> String {
+    let (year, month, day, secs) = civil_utc(time);
+    format!("{:04}-{:02}-{:02} {:02}:{:02} UTC", year, month, day, secs / 3600, secs % 3600 / 60)
+}
+
+/// ISO 8601：`YYYY-MM-DDTHH:MM:SSZ`
+pub(crate) fn format_iso(time: SystemTime) -> String {
+    let (year, month, day, secs) = civil_utc(time);
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", year, month, day, secs / 3600, secs % 3600 / 60, secs % 60)
+}
+
+/// 不引入时间库，按公历把 UTC 时间戳换算成年、月、日与当天的秒数
+fn civil_utc(time: SystemTime) -> (i64, i64, i64, i64) {
+    let secs = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0) as i64;
+    let (days, rem) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+
+    // Howard Hinnant 的 civil_from_days 算法
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day, rem)
+}
+
+/// 每行前加右对齐的行号：`  42 | code`
+fn number_lines(content: &str) -> String {
+    let width = content.lines().count().max(1).to_string().len();
+    let mut out: Vec<String> = content
+        .lines()
+        .enumerate()
+        .map(|(i, line)| format!("{:>width$} | {}", i + 1, line).trim_end().to_string())
+        .collect();
+    if content.ends_with('\n') {
+        out.push(String::new());
+    }
+    out.join("\n")
+}
+
+/// Obsidian 方言下 --collapsible 的折叠 callout 的开头，其后为路径
+pub const CALLOUT_OPEN: &str = "> [!abstract]-";
+
+/// --collapsible 时包住代码块的标签，extract 跳过它与其后的 <summary> 行
+pub const DETAILS_OPEN: &str = "<details>";
+
+/// 文件标题下的校验行 `> SHA-256: <hex>`，extract 据此校验还原的内容
+pub const SHA256_PREFIX: &str = "SHA-256: ";
+
+pub fn write_markdown(writer: &mut impl Write, entries: &[FileEntry], opts: &RenderOptions) -> io::Result<()> {
+    write_document(writer, entries, &sections(entries, opts), opts, None).map(|_| ())
+}
+
+/// --stats-only：只写出 front matter、说明文字与项目摘要，不含目录树与文件内容
+pub fn write_stats(writer: &mut impl Write, entries: &[FileEntry], opts: &RenderOptions) -> io::Result<()> {
+    if let Some(front_matter) = opts.front_matter {
+        write!(writer, "{}", front_matter)?;
+    }
+    if let Some(preamble) = opts.preamble {
+        write!(writer, "{}\n\n", preamble)?;
+    }
+    if let Some(skipped) = opts.summary {
+        write!(writer, "{}", render_summary(entries, skipped, opts))?;
+    }
+    Ok(())
+}
+
+/// 写出一份完整文档。entries 用于目录树与统计，sections 为实际输出的文件小节；
+/// 分卷输出时每一卷都是一份独立文档，nav 为卷间导航行。
+/// 返回各小节代码块内容在文档中的字节范围，供 --manifest 使用
+pub fn write_document(
+    writer: &mut impl Write,
+    entries: &[FileEntry],
+    sections: &[Section],
+    opts: &RenderOptions,
+    nav: Option<&str>,
+) -> io::Result<Vec<Option<Range<u64>>>> {
+    let writer = &mut CountingWriter::new(writer);
+    // 按文档中出现的顺序登记所有标题，保证锚点去重与 GitHub 一致
+    let text = opts.locale.strings();
+    let mut slugger = Slugger::new(opts.dialect);
+    if opts.tree { slugger.slug(text.tree); }
+    if opts.import_graph { slugger.slug(text.imports); }
+    if opts.summary.is_some() { slugger.slug(text.summary); }
+    if opts.token_summary { slugger.slug(text.tokens); }
+    if opts.toc { slugger.slug(text.toc); }
+    if opts.symbols { slugger.slug(text.symbols); }
+    let mut group_anchors: Vec<Option<String>> = Vec::with_capacity(sections.len());
+    let mut dir_anchors: Vec<Option<String>> = Vec::with_capacity(sections.len());
+    let mut anchors: Vec<String> = Vec::with_capacity(sections.len());
+    for (i, section) in sections.iter().enumerate() {
+        let starts_group = starts_group(sections, i);
+        group_anchors.push(starts_group.then(|| slugger.slug(&group_heading(&section.group, opts.locale))));
+        let starts_dir = opts.group_by_dir && starts_dir(sections, i);
+        dir_anchors.push(starts_dir.then(|| slugger.slug(&dir_heading(&section.dir, opts.locale))));
+        anchors.push(slugger.slug(&section.heading()));
+    }
+
+    if let Some(front_matter) = opts.front_matter {
+        write!(writer, "{}", front_matter)?;
+    }
+    if let Some(nav) = nav {
+        writeln!(writer, "{}\n", nav)?;
+    }
+
+    write!(writer, "{}", render_front(entries, opts))?;
+
+    if opts.toc {
+        writeln!(writer, "{} {}\n", opts.hashes(), text.toc)?;
+        for (i, section) in sections.iter().enumerate() {
+            let indent = if section.group.is_empty() { "" } else { "  " };
+            if let Some(anchor) = &group_anchors[i] {
+                writeln!(writer, "- {}", opts.dialect.link(&escape_link_text(&section.group), &group_heading(&section.group, opts.locale), anchor))?;
+            }
+            if let Some(anchor) = &dir_anchors[i] {
+                let heading = dir_heading(&section.dir, opts.locale);
+                writeln!(writer, "{}- {}", indent, opts.dialect.link(&escape_link_text(&heading), &heading, anchor))?;
+            }
+            let nested = if opts.group_by_dir { "  " } else { "" };
+            write!(writer, "{}{}{}", indent, nested, section.toc_line(&anchors[i]))?;
+        }
+        writeln!(writer)?;
+    }
+
+    if opts.symbols {
+        write!(writer, "{}", render_symbol_index(sections, &anchors, opts))?;
+    }
+
+    let mut spans = Vec::with_capacity(sections.len());
+    for (i, section) in sections.iter().enumerate() {
+        if let Some(anchor) = &group_anchors[i] {
+            write!(writer, "{}", opts.dialect.anchor_tag(anchor).unwrap_or_default())?;
+            writeln!(writer, "{} {}\n", "#".repeat(opts.heading_level.saturating_sub(1).max(1)), group_heading(&section.group, opts.locale))?;
+        }
+        if let Some(anchor) = &dir_anchors[i] {
+            write!(writer, "{}", opts.dialect.anchor_tag(anchor).unwrap_or_default())?;
+            writeln!(writer, "{} {}\n", opts.hashes(), dir_heading(&section.dir, opts.locale))?;
+        }
+        write!(writer, "{}", opts.dialect.anchor_tag(&anchors[i]).unwrap_or_default())?;
+        let start = writer.count;
+        spans.push(section.write_to(writer)?.map(|r| start + r.start..start + r.end));
+    }
+    if let Some(skipped) = opts.skipped_files {
+        write!(writer, "{}", render_skipped(skipped, opts))?;
+    }
+    if entries.iter().any(|e| e.sha256.is_some()) {
+        write!(writer, "{}", render_hash_manifest(entries, opts))?;
+    }
+
+    if let Some(nav) = nav {
+        writeln!(writer, "{}", nav)?;
+    }
+    Ok(spans)
+}
+
+/// 分组（根目录）发生变化的位置需要输出一级标题
+fn starts_group(sections: &[Section], i: usize) -> bool {
+    !sections[i].group.is_empty() && (i == 0 || sections[i - 1].group != sections[i].group)
+}
+
+/// 目录或根目录发生变化的位置需要输出目录标题
+fn starts_dir(sections: &[Section], i: usize) -> bool {
+    i == 0 || sections[i - 1].dir != sections[i].dir || sections[i - 1].group != sections[i].group
+}
+
+fn dir_heading(dir: &str, locale: Locale) -> String {
+    format!("{}{}", locale.strings().directory_prefix, if dir.is_empty() { "." } else { dir })
+}
+
+/// 多根目录时每组的标题，extract 依其前缀恢复路径前缀
+fn group_heading(group: &str, locale: Locale) -> String {
+    format!("{}{}", locale.strings().root_prefix, group)
+}
+
+/// 说明文字、目录树与 token 统计等位于文件小节之前、与分卷无关的内容
+pub fn render_front(entries: &[FileEntry], opts: &RenderOptions) -> String {
+    let mut out = String::new();
+    if let Some(preamble) = opts.preamble {
+        out.push_str(&format!("{}\n\n", preamble));
+    }
+    if opts.tree {
+        let paths: Vec<String> = entries.iter().map(FileEntry::display_path).collect();
+        let tree = Tree::from_paths(paths.iter().map(String::as_str));
+        let tree = tree.render(opts.project_name);
+        let fence = fence_for(&tree);
+        out.push_str(&format!("{} {}\n\n{}text\n{}{}\n\n", opts.hashes(), opts.locale.strings().tree, fence, tree, fence));
+    }
+    if opts.import_graph {
+        out.push_str(&render_import_graph(entries, opts));
+    }
+    if let Some(skipped) = opts.summary {
+        out.push_str(&render_summary(entries, skipped, opts));
+    }
+    if opts.token_summary {
+        out.push_str(&render_token_summary(entries, opts));
+    }
+    out
+}
+
+/// 总计、按语言分组的统计表，以及跳过的文件数
+fn render_summary(entries: &[FileEntry], skipped: &SkipCounts, opts: &RenderOptions) -> String {
+    struct Row { language: String, files: usize, lines: usize, bytes: u64 }
+    let text = opts.locale.strings();
+
+    // 占位项已计入跳过的文件数
+    let entries: Vec<&FileEntry> = entries.iter().filter(|e| e.is_included()).collect();
+    let mut rows: Vec<Row> = Vec::new();
+    for entry in &entries {
+        let language = match crate::lang::name(&entry.extension) {
+            Some(name) => name.to_string(),
+            None if entry.extension.is_empty() => text.other.to_string(),
+            None => format!(".{}", entry.extension),
+        };
+        let index = match rows.iter().position(|r| r.language == language) {
+            Some(i) => i,
+            None => {
+                rows.push(Row { language, files: 0, lines: 0, bytes: 0 });
+                rows.len() - 1
+            }
+        };
+        let row = &mut rows[index];
+        row.files += 1;
+        row.lines += entry.line_count();
+        row.bytes += entry.size;
+    }
+    rows.sort_by(|a, b| b.lines.cmp(&a.lines).then_with(|| a.language.cmp(&b.language)));
+
+    let lines: usize = rows.iter().map(|r| r.lines).sum();
+    let bytes: u64 = rows.iter().map(|r| r.bytes).sum();
+    let mut totals = format!(
+        "- {}{}{}\n- {}{}{}\n- {}{}{}\n",
+        text.files,
+        text.colon,
+        entries.len(),
+        text.lines,
+        text.colon,
+        lines,
+        text.size,
+        text.colon,
+        HumanBytes(bytes)
+    );
+    // 统计过 token 时一并给出估算
+    if entries.iter().any(|e| e.tokens.is_some()) {
+        totals.push_str(&format!("- {}{}{}\n", text.token_count, text.colon, entries.iter().filter_map(|e| e.tokens).sum::<usize>()));
+    }
+    let mut out = format!(
+        "{} {}\n\n{}\n| {} | {} | {} | {} |\n| --- | ---: | ---: | ---: |\n",
+        opts.hashes(),
+        text.summary,
+        totals,
+        text.language,
+        text.files,
+        text.lines,
+        text.size
+    );
+    for row in &rows {
+        out.push_str(&format!(
+            "| {} | {} | {} | {} |\n",
+            row.language.replace('|', "\\|"),
+            row.files,
+            row.lines,
+            HumanBytes(row.bytes)
+        ));
+    }
+    out.push('\n');
+
+    let reasons: Vec<(Skip, usize)> = Skip::ALL
+        .iter()
+        .map(|&r| (r, skipped.get(r)))
+        .filter(|(_, n)| *n > 0)
+        .collect();
+    if !reasons.is_empty() {
+        out.push_str(&format!("| {} | {} |\n| --- | ---: |\n", text.skipped, text.files));
+        for (reason, n) in reasons {
+            out.push_str(&format!("| {} | {} |\n", opts.locale.describe(reason), n));
+        }
+        out.push('\n');
+    }
+
+    let secrets = skipped.secrets();
+    if !secrets.is_empty() {
+        out.push_str(&format!("{}\n\n", text.secrets));
+        for path in secrets {
+            out.push_str(&format!("- `{}`\n", path));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// 文件小节之后的跳过清单；被忽略的目录以 / 结尾，其中的文件不再逐个列出
+pub fn render_skipped(skipped: &SkipCounts, opts: &RenderOptions) -> String {
+    let text = opts.locale.strings();
+    let files = skipped.files();
+    let mut out = format!("{} {}\n\n", opts.hashes(), text.skipped);
+    if files.is_empty() {
+        out.push_str(&format!("{}\n\n", text.none));
+        return out;
+    }
+    out.push_str(&format!("| {} | {} |\n| --- | --- |\n", text.file, text.reason));
+    for (path, reason) in files {
+        out.push_str(&format!("| `{}` | {} |\n", path.replace('|', "\\|"), opts.locale.describe(reason)));
+    }
+    out.push('\n');
+    out
+}
+
+/// 文档末尾的校验清单：每个收录内容的文件及其 SHA-256
+fn render_hash_manifest(entries: &[FileEntry], opts: &RenderOptions) -> String {
+    let text = opts.locale.strings();
+    let mut out = format!("{} {}\n\n| {} | SHA-256 |\n| --- | --- |\n", opts.hashes(), text.hashes, text.file);
+    for entry in entries {
+        if let Some(hash) = &entry.sha256 {
+            out.push_str(&format!("| `{}` | `{}` |\n", entry.display_path().replace('|', "\\|"), hash));
+        }
+    }
+    out.push('\n');
+    out
+}
+
+/// 文件间引用关系的 Mermaid 流程图，只画出引用了或被引用的文件
+fn render_import_graph(entries: &[FileEntry], opts: &RenderOptions) -> String {
+    let text = opts.locale.strings();
+    let edges = crate::deps::edges(entries);
+    let mut out = format!("{} {}\n\n", opts.hashes(), text.imports);
+    if edges.is_empty() {
+        out.push_str(&format!("{}\n\n", text.none));
+        return out;
+    }
+    let mut nodes: Vec<usize> = edges.iter().flat_map(|&(from, to)| [from, to]).collect();
+    nodes.sort_unstable();
+    nodes.dedup();
+    out.push_str("```mermaid\ngraph LR\n");
+    for (id, &i) in nodes.iter().enumerate() {
+        out.push_str(&format!("  n{}[\"{}\"]\n", id, entries[i].display_path().replace('"', "#quot;")));
+    }
+    let id = |i: usize| nodes.binary_search(&i).unwrap_or_default();
+    for (from, to) in edges {
+        out.push_str(&format!("  n{} --> n{}\n", id(from), id(to)));
+    }
+    out.push_str("```\n\n");
+    out
+}
+
+/// 目录之后的符号索引：本文档各小节中的顶层定义按名称排序，链接到所在小节
+fn render_symbol_index(sections: &[Section], anchors: &[String], opts: &RenderOptions) -> String {
+    let mut rows: Vec<(String, usize, String)> = Vec::new();
+    for (i, section) in sections.iter().enumerate() {
+        for (symbol, row) in section.symbols.iter().zip(section.symbol_rows(&anchors[i])) {
+            rows.push((symbol.name.to_lowercase(), i, row));
+        }
+    }
+    rows.sort();
+
+    let text = opts.locale.strings();
+    let mut out = format!("{} {}\n\n", opts.hashes(), text.symbols);
+    if rows.is_empty() {
+        out.push_str(&format!("{}\n\n", text.none));
+        return out;
+    }
+    out.push_str(&format!("| {} | {} | {} |\n| --- | --- | --- |\n", text.symbol, text.kind, text.file));
+    for (_, _, row) in rows {
+        out.push_str(&row);
+    }
+    out.push('\n');
+    out
+}
+
+/// 按 token 数从多到少列出文件，便于找出占用上下文最多的文件
+fn render_token_summary(entries: &[FileEntry], opts: &RenderOptions) -> String {
+    let text = opts.locale.strings();
+    let mut sorted: Vec<&FileEntry> = entries.iter().collect();
+    sorted.sort_by_key(|e| std::cmp::Reverse(e.tokens.unwrap_or(0)));
+
+    let mut out = format!("{} {}\n\n| {} | {} |\n| --- | ---: |\n", opts.hashes(), text.tokens, text.file, text.token_count);
+    for entry in sorted {
+        out.push_str(&format!("| {} | {} |\n", entry.display_path().replace('|', "\\|"), entry.tokens.unwrap_or(0)));
+    }
+    out.push_str(&format!("| **{}** | **{}** |\n\n", text.total, crate::tokens::total(entries)));
+    out
+}
+
+fn escape_link_text(text: &str) -> String {
+    text.replace('[', "\\[").replace(']', "\\]")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::{Scanned, Stream};
+
+    #[test]
+    fn fence_outgrows_backtick_runs_and_streaming_agrees() {
+        let dir = tempfile::tempdir().unwrap();
+        for (content, expected) in [
+            ("plain\n", "```"),
+            ("inline `code` and ``two``\n", "```"),
+            ("```rust\nfn main() {}\n```\n", "````"),
+            ("a\n`````\nb ```` c\n", "``````"),
+        ] {
+            assert_eq!(fence_for(content), expected, "{:?}", content);
+            let path = dir.path().join("file.md");
+            std::fs::write(&path, content).unwrap();
+            let Scanned::Ready { stream, .. } = Stream::scan(&path, false, None).unwrap() else { panic!("{:?}", content) };
+            assert_eq!(fence(stream.longest_backticks), expected, "{:?}", content);
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::thread;
+
+use crate::watch;
+
+/// 页面每秒询问一次版本号，与载入时不同就刷新，并保持滚动位置
+const RELOAD_SCRIPT: &str = r#"<script>
+(function () {
+  var version = "{version}";
+  var key = "code2md-scroll";
+  var saved = sessionStorage.getItem(key);
+  if (saved !== null) { sessionStorage.removeItem(key); window.addEventListener("load", function () { window.scrollTo(0, +saved); }); }
+  setInterval(function () {
+    fetch("/__version", { cache: "no-store" }).then(function (r) { return r.text(); }).then(function (v) {
+      if (v !== version) { sessionStorage.setItem(key, String(window.scrollY)); location.reload(); }
+    }).catch(function () {});
+  }, 1000);
+})();
+</script>
+"#;
+
+/// 当前提供的页面及其版本号，每次重新生成后加一
+struct Page {
+    version: u64,
+    html: String,
+}
+
+/// 在 127.0.0.1:port 上提供 render 生成的 HTML，源码变更时重新生成，已打开的页面自动刷新。
+/// 重新生成失败时继续提供上一版页面
+pub fn serve(
+    port: u16,
+    roots: &[PathBuf],
+    is_relevant: impl Fn(&Path) -> bool,
+    render: impl Fn() -> code2xml::Result<String>,
+) -> code2xml::Result<()> {
+    let page = Arc::new(RwLock::new(Page { version: 1, html: render()? }));
+    let listener = TcpListener::bind(("127.0.0.1", port))?;
+    status!("预览地址：http://{}/", listener.local_addr()?);
+
+    let shared = Arc::clone(&page);
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let page = Arc::clone(&shared);
+            thread::spawn(move || {
+                // 浏览器中途断开等错误只影响这一个连接
+                let _ = respond(stream, &page);
+            });
+        }
+    });
+
+    watch::watch(roots, is_relevant, || match render() {
+        Ok(html) => {
+            let mut page = page.write().unwrap_or_else(|e| e.into_inner());
+            page.version += 1;
+            page.html = html;
+            status!("已重新生成");
+        }
+        Err(e) => eprintln!("重新生成失败：{}", e),
+    })?;
+    Ok(())
+}
+
+fn respond(stream: TcpStream, page: &RwLock<Page>) -> io::Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut request = String::new();
+    reader.read_line(&mut request)?;
+    // 丢弃其余请求头
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+    let mut parts = request.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    let path = target.split('?').next().unwrap_or("");
+
+    let page = page.read().unwrap_or_else(|e| e.into_inner());
+    let (status, content_type, body) = match (method, path) {
+        ("GET", "/") => ("200 OK", "text/html; charset=utf-8", with_reload(&page.html, page.version)),
+        ("GET", "/__version") => ("200 OK", "text/plain", page.version.to_string()),
+        ("GET", _) => ("404 Not Found", "text/plain", "Not Found".to_string()),
+        _ => ("405 Method Not Allowed", "text/plain", "Method Not Allowed".to_string()),
+    };
+    drop(page);
+
+    let mut stream = reader.into_inner();
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    )?;
+    stream.write_all(body.as_bytes())?;
+    stream.flush()
+}
+
+/// 在 </body> 前插入刷新脚本
+fn with_reload(html: &str, version: u64) -> String {
+    let script = RELOAD_SCRIPT.replace("{version}", &version.to_string());
+    match html.rfind("</body>") {
+        Some(at) => format!("{}{}{}", &html[..at], script, &html[at..]),
+        None => format!("{}{}", html, script),
+    }
+}
@@ -0,0 +1,37 @@
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+use std::io;
+
+use crate::render::FileEntry;
+
+/// 为收录内容的文件计算 SHA-256；流式读取的文件再读一遍源文件
+pub fn hash_entries(entries: &mut [FileEntry]) -> io::Result<()> {
+    entries.par_iter_mut().filter(|e| e.is_included()).try_for_each(|entry| {
+        entry.sha256 = Some(match &entry.stream {
+            Some(stream) => stream.sha256()?,
+            None => hex(entry.content.as_bytes()),
+        });
+        Ok(())
+    })
+}
+
+/// data 的 SHA-256，小写十六进制
+pub fn hex(data: &[u8]) -> String {
+    finish(Sha256::new_with_prefix(data))
+}
+
+/// 分块输入的 hasher 的摘要，小写十六进制
+pub fn finish(hasher: Sha256) -> String {
+    hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_digests() {
+        assert_eq!(hex(b""), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+        assert_eq!(hex(b"abc"), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+    }
+}
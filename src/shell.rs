@@ -0,0 +1,98 @@
+//! 资源管理器右键菜单：在当前用户的注册表中为文件夹添加“转换为 Markdown”一项，
+//! 不需要管理员权限。菜单项以 --console 启动本程序，完成后窗口停留以便查看结果。
+
+use std::io;
+
+/// 右键文件夹与在文件夹空白处右键两处菜单；后者用 %V 取得当前目录
+#[cfg(windows)]
+const KEYS: [(&str, &str); 2] = [
+    (r"Software\Classes\Directory\shell\code2md", "%1"),
+    (r"Software\Classes\Directory\Background\shell\code2md", "%V"),
+];
+
+#[cfg(windows)]
+const LABEL: &str = "转换为 Markdown";
+
+#[cfg(not(windows))]
+pub fn install() -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "右键菜单仅支持 Windows"))
+}
+
+#[cfg(not(windows))]
+pub fn uninstall() -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "右键菜单仅支持 Windows"))
+}
+
+#[cfg(windows)]
+pub fn install() -> io::Result<()> {
+    let exe = std::env::current_exe()?;
+    let exe = exe.display();
+    for (key, arg) in KEYS {
+        imp::set_value(key, None, LABEL)?;
+        imp::set_value(key, Some("Icon"), &format!("\"{}\",0", exe))?;
+        imp::set_value(&format!(r"{}\command", key), None, &format!("\"{}\" --console \"{}\"", exe, arg))?;
+    }
+    status!("已添加右键菜单“{}”（uninstall-shell 可移除）", LABEL);
+    Ok(())
+}
+
+#[cfg(windows)]
+pub fn uninstall() -> io::Result<()> {
+    for (key, _) in KEYS {
+        imp::delete_tree(key)?;
+    }
+    status!("已移除右键菜单");
+    Ok(())
+}
+
+#[cfg(windows)]
+mod imp {
+    use std::io;
+    use std::ptr::null_mut;
+
+    use windows_sys::Win32::Foundation::{ERROR_FILE_NOT_FOUND, WIN32_ERROR};
+    use windows_sys::Win32::System::Registry::{
+        RegCloseKey, RegCreateKeyW, RegDeleteTreeW, RegSetValueExW, HKEY, HKEY_CURRENT_USER, REG_SZ,
+    };
+
+    fn wide(text: &str) -> Vec<u16> {
+        text.encode_utf16().chain(Some(0)).collect()
+    }
+
+    fn check(code: WIN32_ERROR) -> io::Result<()> {
+        match code {
+            0 => Ok(()),
+            code => Err(io::Error::from_raw_os_error(code as i32)),
+        }
+    }
+
+    /// 在 HKEY_CURRENT_USER 下创建键（已存在则打开）并写入字符串值；name 为 None 时写默认值
+    pub fn set_value(key: &str, name: Option<&str>, value: &str) -> io::Result<()> {
+        let key = wide(key);
+        let name = name.map(wide);
+        let value = wide(value);
+        unsafe {
+            let mut handle: HKEY = null_mut();
+            check(RegCreateKeyW(HKEY_CURRENT_USER, key.as_ptr(), &mut handle))?;
+            let result = check(RegSetValueExW(
+                handle,
+                name.as_ref().map_or(std::ptr::null(), |n| n.as_ptr()),
+                0,
+                REG_SZ,
+                value.as_ptr().cast(),
+                (value.len() * 2) as u32,
+            ));
+            RegCloseKey(handle);
+            result
+        }
+    }
+
+    /// 删除键及其全部子键；键本就不存在时视为成功
+    pub fn delete_tree(key: &str) -> io::Result<()> {
+        let key = wide(key);
+        match unsafe { RegDeleteTreeW(HKEY_CURRENT_USER, key.as_ptr()) } {
+            ERROR_FILE_NOT_FOUND => Ok(()),
+            code => check(code),
+        }
+    }
+}
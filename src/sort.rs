@@ -0,0 +1,82 @@
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::cmp::Ordering;
+
+use crate::render::FileEntry;
+
+/// 文件在文档中的排列顺序；多根目录时只在各根目录内部排序
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    /// 按路径逐级比较，与机器和文件系统无关
+    #[default]
+    Alpha,
+    /// 从小到大
+    Size,
+    /// 从旧到新
+    Mtime,
+    /// 按扩展名分组
+    Ext,
+    /// 说明文档、清单文件与入口文件在前，其余按路径；上下文被截断时重要文件得以保留
+    Smart,
+    /// 保持遍历顺序（取决于文件系统）
+    None,
+}
+
+pub fn sort_entries(entries: &mut [FileEntry], order: SortOrder) {
+    if order == SortOrder::None {
+        return;
+    }
+    // 同值时按路径排列，保证结果稳定
+    for group in entries.chunk_by_mut(|a, b| a.root == b.root) {
+        group.sort_by(|a, b| {
+            let by_key = match order {
+                SortOrder::Size => a.size.cmp(&b.size),
+                SortOrder::Mtime => a.modified.cmp(&b.modified),
+                SortOrder::Ext => a.extension.cmp(&b.extension),
+                SortOrder::Smart => importance(a).cmp(&importance(b)),
+                SortOrder::Alpha | SortOrder::None => Ordering::Equal,
+            };
+            by_key.then_with(|| a.rel_path.split('/').cmp(b.rel_path.split('/')))
+        });
+    }
+}
+
+/// 把同一目录下的文件排到一起（根目录下的文件在前），目录内保持原有顺序
+pub fn group_by_dir(entries: &mut [FileEntry]) {
+    fn dir(entry: &FileEntry) -> &str {
+        entry.rel_path.rsplit_once('/').map_or("", |(dir, _)| dir)
+    }
+    for group in entries.chunk_by_mut(|a, b| a.root == b.root) {
+        group.sort_by(|a, b| dir(a).split('/').cmp(dir(b).split('/')));
+    }
+}
+
+/// README 一类的说明文档
+const READMES: &[&str] = &["readme", "contributing", "architecture", "overview"];
+
+/// 描述项目与依赖的清单文件
+const MANIFESTS: &[&str] = &[
+    "cargo.toml", "package.json", "pyproject.toml", "setup.py", "setup.cfg", "requirements.txt",
+    "go.mod", "pom.xml", "build.gradle", "build.gradle.kts", "gemfile", "composer.json",
+    "cmakelists.txt", "makefile", "dockerfile", "deno.json", "mix.exs", "pubspec.yaml",
+];
+
+/// 常见的程序入口文件（不含扩展名）
+const ENTRY_POINTS: &[&str] = &["main", "lib", "index", "app", "server", "__main__", "program"];
+
+/// 越小越重要：说明文档、清单、入口文件、其余文件；同一类中层级浅的在前
+fn importance(entry: &FileEntry) -> (u8, usize) {
+    let name = entry.rel_path.rsplit('/').next().unwrap_or_default().to_lowercase();
+    let stem = name.split('.').next().unwrap_or_default();
+    let rank = if READMES.contains(&stem) {
+        0
+    } else if MANIFESTS.contains(&name.as_str()) {
+        1
+    } else if ENTRY_POINTS.contains(&stem) && !entry.extension.is_empty() {
+        2
+    } else {
+        3
+    };
+    (rank, entry.rel_path.matches('/').count())
+}
@@ -0,0 +1,221 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::render::{self, FileEntry, RenderOptions, Section};
+use crate::tokens;
+
+/// 每一卷的容量上限
+#[derive(Clone, Copy, Debug)]
+pub enum Limit {
+    Tokens(usize),
+    Bytes(usize),
+}
+
+impl Limit {
+    fn measure(&self, text: &str) -> usize {
+        match self {
+            Limit::Tokens(_) => tokens::count(text),
+            Limit::Bytes(_) => text.len(),
+        }
+    }
+
+    fn capacity(&self) -> usize {
+        match self {
+            Limit::Tokens(n) | Limit::Bytes(n) => *n,
+        }
+    }
+}
+
+/// 第 n 卷的文件路径：`project.md` -> `project.part{n}.md`
+pub fn part_path(output: &Path, n: usize) -> PathBuf {
+    let stem = output.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match output.extension() {
+        Some(ext) => format!("{}.part{}.{}", stem, n, ext.to_string_lossy()),
+        None => format!("{}.part{}", stem, n),
+    };
+    output.with_file_name(name)
+}
+
+/// 判断文件名是否为 output 的某一卷，用于在再次扫描时排除
+pub fn is_part_of(output: &Path, name: &str) -> bool {
+    let stem = output.file_stem().unwrap_or_default().to_string_lossy();
+    let suffix = output.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+    name.strip_prefix(&format!("{}.part", stem))
+        .and_then(|rest| rest.strip_suffix(suffix.as_str()))
+        .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// 按容量把文件小节分到若干卷中，并写出 `project.partN.md`。
+/// 文件不会跨卷，除非单个文件本身就超过容量，此时按行切开。
+pub fn write_parts(
+    output: &Path,
+    entries: &[FileEntry],
+    opts: &RenderOptions,
+    limit: Limit,
+) -> io::Result<Vec<PathBuf>> {
+    let parts = plan(output, entries, opts, limit);
+    let total = parts.len();
+    let mut written = Vec::with_capacity(total);
+
+    for (i, sections) in parts.iter().enumerate() {
+        let n = i + 1;
+        let path = part_path(output, n);
+        let nav = nav_line(output, n, total);
+        // 目录树与统计只放在第一卷，跳过清单只放在最后一卷
+        let part_opts = RenderOptions {
+            tree: opts.tree && n == 1,
+            import_graph: opts.import_graph && n == 1,
+            token_summary: opts.token_summary && n == 1,
+            summary: opts.summary.filter(|_| n == 1),
+            skipped_files: opts.skipped_files.filter(|_| n == total),
+            ..*opts
+        };
+
+        let mut writer = BufWriter::new(File::create(&path)?);
+        render::write_document(&mut writer, entries, sections, &part_opts, Some(&nav))?;
+        writer.flush()?;
+        written.push(path);
+    }
+
+    // 清理上一次运行留下的多余分卷
+    let mut n = total + 1;
+    while part_path(output, n).is_file() {
+        std::fs::remove_file(part_path(output, n))?;
+        n += 1;
+    }
+    Ok(written)
+}
+
+fn plan(output: &Path, entries: &[FileEntry], opts: &RenderOptions, limit: Limit) -> Vec<Vec<Section>> {
+    let capacity = limit.capacity();
+    // 导航行出现在每卷首尾，按最坏情况的卷号估算
+    let overhead = 2 * limit.measure(&nav_line(output, 9998, 9999)) + opts.front_matter.map_or(0, |f| limit.measure(f));
+    let front = limit.measure(&render::render_front(entries, opts));
+
+    let mut parts: Vec<Vec<Section>> = vec![Vec::new()];
+    let mut used = overhead + front;
+
+    for section in render::sections(entries, opts) {
+        for piece in fit(section, capacity.saturating_sub(overhead), limit, opts.toc) {
+            let cost = section_cost(&piece, limit, opts.toc);
+            if used + cost > capacity && !parts.last().is_some_and(|p| p.is_empty()) {
+                parts.push(Vec::new());
+                used = overhead;
+            }
+            used += cost;
+            parts.last_mut().unwrap().push(piece);
+        }
+    }
+    // 跳过清单跟在最后一卷的文件之后，放不下时单独成卷
+    if let Some(skipped) = opts.skipped_files {
+        if used + limit.measure(&render::render_skipped(skipped, opts)) > capacity && !parts.last().is_some_and(|p| p.is_empty()) {
+            parts.push(Vec::new());
+        }
+    }
+    parts
+}
+
+fn section_cost(section: &Section, limit: Limit, toc: bool) -> usize {
+    let mut cost = limit.measure(&section.render());
+    if toc {
+        cost += limit.measure(&section.toc_line(&section.heading()));
+    }
+    cost += section.symbol_rows(&section.heading()).iter().map(|row| limit.measure(row)).sum::<usize>();
+    cost
+}
+
+/// 单个小节超过可用容量时按行切成若干段，标题注明 (i/n)
+fn fit(section: Section, available: usize, limit: Limit, toc: bool) -> Vec<Section> {
+    if section_cost(&section, limit, toc) <= available {
+        return vec![section];
+    }
+
+    let empty = Section {
+        group: section.group.clone(),
+        label: section.label.clone(),
+        dir: section.dir.clone(),
+        level: section.level,
+        template: section.template.clone(),
+        collapsible: section.collapsible,
+        quoted: section.quoted,
+        dialect: section.dialect,
+        locale: section.locale,
+        escaped: section.escaped,
+        language: section.language.clone(),
+        meta: section.meta.clone(),
+        content: String::new(),
+        duplicate_of: None,
+        placeholder: None,
+        stream: None,
+        sha256: section.sha256.clone(),
+        symbols: section.symbols.clone(),
+    };
+    let base = section_cost(&empty, limit, toc) + 8;
+    let budget = available.saturating_sub(base).max(1);
+
+    let mut chunks: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut used = 0;
+    // 各行保留原有的行尾，extract 拼接各段后与原内容逐字节一致
+    for line in section.content.split_inclusive('\n') {
+        let cost = limit.measure(line);
+        if used + cost > budget && !current.is_empty() {
+            chunks.push(std::mem::take(&mut current));
+            used = 0;
+        }
+        current.push_str(line);
+        used += cost;
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+
+    let count = chunks.len();
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(i, mut content)| {
+            // 段与段之间的换行由 extract 拼接时补回
+            if i + 1 < count {
+                content.pop();
+            }
+            Section {
+                group: section.group.clone(),
+                label: format!("{} ({}/{})", section.label, i + 1, count),
+                dir: section.dir.clone(),
+                level: section.level,
+                template: section.template.clone(),
+                collapsible: section.collapsible,
+                quoted: section.quoted,
+                dialect: section.dialect,
+                locale: section.locale,
+                escaped: section.escaped,
+                language: section.language.clone(),
+                // 文件信息只写在第一段下
+                meta: if i == 0 { section.meta.clone() } else { None },
+                content,
+                duplicate_of: None,
+                placeholder: None,
+                stream: None,
+                sha256: if i == 0 { section.sha256.clone() } else { None },
+                symbols: if i == 0 { section.symbols.clone() } else { Vec::new() },
+            }
+        })
+        .collect()
+}
+
+fn nav_line(output: &Path, n: usize, total: usize) -> String {
+    let link = |k: usize| {
+        let name = part_path(output, k).file_name().unwrap_or_default().to_string_lossy().into_owned();
+        format!("[{}]({})", name, name.replace(' ', "%20"))
+    };
+    let mut nav = format!("> Part {} of {}", n, total);
+    if n > 1 {
+        nav.push_str(&format!(" | Previous: {}", link(n - 1)));
+    }
+    if n < total {
+        nav.push_str(&format!(" | Next: {}", link(n + 1)));
+    }
+    nav
+}
@@ -0,0 +1,86 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// 文件未被收录的原因
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Skip {
+    /// 被忽略的文件名或 --exclude 模式
+    Ignored,
+    /// 不匹配 --include 模式
+    NotIncluded,
+    /// 被忽略的扩展名
+    Extension,
+    /// 超过单文件大小上限
+    TooLarge,
+    /// 二进制文件
+    Binary,
+    /// 内容为空
+    Empty,
+    /// 读取失败
+    Unreadable,
+    /// 疑似密钥文件（--skip-secrets）
+    Secret,
+}
+
+impl Skip {
+    pub const ALL: [Skip; 8] = [
+        Skip::Ignored,
+        Skip::NotIncluded,
+        Skip::Extension,
+        Skip::TooLarge,
+        Skip::Binary,
+        Skip::Empty,
+        Skip::Unreadable,
+        Skip::Secret,
+    ];
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            Skip::Ignored => "Ignored name or exclude pattern",
+            Skip::NotIncluded => "Not matched by include patterns",
+            Skip::Extension => "Ignored extension",
+            Skip::TooLarge => "Over size limit",
+            Skip::Binary => "Binary",
+            Skip::Empty => "Empty",
+            Skip::Unreadable => "Unreadable",
+            Skip::Secret => "Likely secrets",
+        }
+    }
+}
+
+/// 按原因统计被跳过的文件，可在并行遍历中共享。
+/// 被整体剪掉的目录（忽略目录、.gitignore）不会逐个计入其中的文件；
+/// 被名称规则剪掉的目录本身以 / 结尾记入清单，但不计数
+#[derive(Default)]
+pub struct SkipCounts {
+    counts: [AtomicUsize; Skip::ALL.len()],
+    /// 被跳过的文件（显示路径）及原因，供摘要与跳过清单逐个列出
+    files: Mutex<Vec<(String, Skip)>>,
+}
+
+impl SkipCounts {
+    pub fn add(&self, reason: Skip, path: String) {
+        self.counts[reason as usize].fetch_add(1, Ordering::Relaxed);
+        self.files.lock().unwrap().push((path, reason));
+    }
+
+    pub fn add_dir(&self, path: String) {
+        self.files.lock().unwrap().push((format!("{}/", path), Skip::Ignored));
+    }
+
+    pub fn get(&self, reason: Skip) -> usize {
+        self.counts[reason as usize].load(Ordering::Relaxed)
+    }
+
+    /// 按路径排序的全部跳过项
+    pub fn files(&self) -> Vec<(String, Skip)> {
+        let mut files = self.files.lock().unwrap().clone();
+        files.sort();
+        files
+    }
+
+    /// 按路径排序的疑似密钥文件
+    pub fn secrets(&self) -> Vec<String> {
+        self.files().into_iter().filter(|(_, r)| *r == Skip::Secret).map(|(p, _)| p).collect()
+    }
+}
@@ -0,0 +1,139 @@
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::encoding;
+use crate::pathname;
+use crate::redact;
+use crate::sha256;
+
+/// 不小于这个大小的文件在条件允许时不读入内存，写文档时才流式读取
+pub const STREAM_THRESHOLD: u64 = 256 * 1024;
+
+/// 写文档时才读取的文件内容；渲染所需的行数与围栏长度由 scan 预先得出
+#[derive(Clone)]
+pub struct Stream {
+    path: PathBuf,
+    /// 与 str::lines 一致的行数
+    pub lines: usize,
+    /// 内容中最长的连续反引号，决定代码块围栏
+    pub longest_backticks: usize,
+    normalize_eol: bool,
+    /// 写出时逐行遮盖疑似密钥所依据的文件名，None 为不遮盖
+    redact: Option<String>,
+    line_numbers: bool,
+}
+
+/// Stream::scan 的结果
+pub enum Scanned {
+    /// 只含空白
+    Empty,
+    /// 含私钥块：遮盖与 --skip-secrets 都要整段匹配，改为读入内存处理
+    PrivateKey,
+    Ready {
+        stream: Stream,
+        /// 写出时将遮盖的处数
+        redacted: usize,
+        /// 第一行，用于按 shebang 判断语言
+        first_line: String,
+        /// 混用了 CRLF 与 LF
+        mixed_eol: bool,
+    },
+}
+
+impl Stream {
+    /// 流式读一遍文件，得出渲染所需的信息而不保留内容
+    pub fn scan(path: &Path, normalize_eol: bool, redact: Option<String>) -> io::Result<Scanned> {
+        let mut lines = 0;
+        let mut longest_backticks = 0;
+        let mut blank = true;
+        let mut private_key = false;
+        let mut redacted = 0;
+        let (mut crlf, mut lf) = (0, 0);
+        let mut first_line = None;
+        let mut ends_with_newline = true;
+        encoding::for_each_line(File::open(pathname::extended(path))?, |line| {
+            if line.ends_with("\r\n") { crlf += 1; } else if line.ends_with('\n') { lf += 1; }
+            private_key |= line.contains("-----BEGIN ") && line.contains("PRIVATE KEY");
+            let (text, count) = transform(line, normalize_eol, redact.as_deref());
+            if text.is_empty() { return Ok(()); }
+            first_line.get_or_insert_with(|| text.to_string());
+            redacted += count;
+            blank &= text.trim().is_empty();
+            lines += text.matches('\n').count();
+            ends_with_newline = text.ends_with('\n');
+            let run = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+            longest_backticks = longest_backticks.max(run);
+            Ok(())
+        })?;
+        if !ends_with_newline {
+            lines += 1;
+        }
+
+        if blank {
+            return Ok(Scanned::Empty);
+        }
+        if private_key {
+            return Ok(Scanned::PrivateKey);
+        }
+        let stream = Stream { path: path.to_path_buf(), lines, longest_backticks, normalize_eol, redact, line_numbers: false };
+        Ok(Scanned::Ready { stream, redacted, first_line: first_line.unwrap_or_default(), mixed_eol: crlf > 0 && lf > 0 })
+    }
+
+    /// 写出时每行加上行号
+    pub fn with_line_numbers(mut self, line_numbers: bool) -> Stream {
+        self.line_numbers = line_numbers;
+        self
+    }
+
+    /// 写出的内容（不含行号）的 SHA-256
+    pub fn sha256(&self) -> io::Result<String> {
+        let mut hasher = Sha256::new();
+        encoding::for_each_line(File::open(pathname::extended(&self.path))?, |line| {
+            hasher.update(transform(line, self.normalize_eol, self.redact.as_deref()).0.as_bytes());
+            Ok(())
+        })?;
+        Ok(sha256::finish(hasher))
+    }
+
+    /// 写出遮盖与换行统一之后的内容；行号的格式与 render::number_lines 一致
+    pub fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        let width = self.lines.max(1).to_string().len();
+        let mut number = 0;
+        encoding::for_each_line(File::open(pathname::extended(&self.path))?, |line| {
+            let (text, _) = transform(line, self.normalize_eol, self.redact.as_deref());
+            if !self.line_numbers {
+                return writer.write_all(text.as_bytes());
+            }
+            for line in text.split_inclusive('\n') {
+                number += 1;
+                let body = line.strip_suffix('\n').unwrap_or(line);
+                let body = body.strip_suffix('\r').unwrap_or(body);
+                write!(writer, "{}", format!("{:>width$} | {}", number, body).trim_end())?;
+                if line.ends_with('\n') {
+                    writer.write_all(b"\n")?;
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+/// 对一行依次统一换行符、遮盖疑似密钥，返回结果与遮盖处数
+fn transform<'a>(line: &'a str, normalize_eol: bool, redact: Option<&str>) -> (Cow<'a, str>, usize) {
+    let mut text = Cow::Borrowed(line);
+    if normalize_eol && text.contains('\r') {
+        text = Cow::Owned(text.replace("\r\n", "\n").replace('\r', "\n"));
+    }
+    let mut count = 0;
+    if let Some(file_name) = redact {
+        let (masked, n) = redact::redact(&text, file_name);
+        if n > 0 {
+            text = Cow::Owned(masked);
+            count = n;
+        }
+    }
+    (text, count)
+}
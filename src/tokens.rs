@@ -0,0 +1,28 @@
+use rayon::prelude::*;
+use std::io::{self, Write};
+
+use crate::render::FileEntry;
+
+/// 使用 cl100k_base 编码估算 token 数，与 GPT-4 系列模型一致
+pub fn count(text: &str) -> usize {
+    tiktoken_rs::cl100k_base_singleton().encode_ordinary(text).len()
+}
+
+pub fn count_entries(entries: &mut [FileEntry]) {
+    // 重复文件只输出一行引用，不计入
+    entries.par_iter_mut().for_each(|entry| {
+        entry.tokens = Some(if entry.duplicate_of.is_some() || entry.placeholder.is_some() { 0 } else { count(&entry.content) });
+    });
+}
+
+pub fn total(entries: &[FileEntry]) -> usize {
+    entries.iter().filter_map(|e| e.tokens).sum()
+}
+
+/// 按文件列出 token 数并给出合计，写到 stderr
+pub fn report(out: &mut impl Write, entries: &[FileEntry]) -> io::Result<()> {
+    for entry in entries {
+        writeln!(out, "{:>10}  {}", entry.tokens.unwrap_or(0), entry.display_path())?;
+    }
+    writeln!(out, "{:>10}  total", total(entries))
+}
@@ -0,0 +1,76 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+/// 由相对路径构建的目录树，目录在前、文件在后，各自按名称排序
+#[derive(Default)]
+pub struct Tree {
+    dirs: BTreeMap<String, Tree>,
+    files: BTreeSet<String>,
+}
+
+impl Tree {
+    pub fn from_paths<'a>(paths: impl IntoIterator<Item = &'a str>) -> Tree {
+        let mut root = Tree::default();
+        for path in paths {
+            let mut node = &mut root;
+            let mut parts = path.split('/').peekable();
+            while let Some(part) = parts.next() {
+                if parts.peek().is_some() {
+                    node = node.dirs.entry(part.to_string()).or_default();
+                } else {
+                    node.files.insert(part.to_string());
+                }
+            }
+        }
+        root
+    }
+
+    /// 渲染为类似 `tree` 命令的文本，第一行为根目录名
+    pub fn render(&self, root_name: &str) -> String {
+        let mut out = format!("{}/\n", root_name);
+        self.render_children("", &mut out);
+        out
+    }
+
+    /// 渲染为嵌套的 `<ul>`，文件链接由 link 根据完整相对路径给出
+    pub fn render_html(&self, link: &dyn Fn(&str) -> String) -> String {
+        let mut out = String::new();
+        self.render_html_children("", link, &mut out);
+        out
+    }
+
+    fn render_html_children(&self, prefix: &str, link: &dyn Fn(&str) -> String, out: &mut String) {
+        use crate::format::html::escape;
+
+        out.push_str("<ul>\n");
+        for (name, child) in &self.dirs {
+            let _ = writeln!(out, "<li><span class=\"dir\">{}/</span>", escape(name));
+            child.render_html_children(&format!("{}{}/", prefix, name), link, out);
+            out.push_str("</li>\n");
+        }
+        for name in &self.files {
+            let path = format!("{}{}", prefix, name);
+            let _ = writeln!(out, "<li><a href=\"{}\">{}</a></li>", escape(&link(&path)), escape(name));
+        }
+        out.push_str("</ul>\n");
+    }
+
+    fn render_children(&self, prefix: &str, out: &mut String) {
+        let total = self.dirs.len() + self.files.len();
+        let mut index = 0;
+
+        for (name, child) in &self.dirs {
+            index += 1;
+            let last = index == total;
+            let _ = writeln!(out, "{}{}{}/", prefix, if last { "└── " } else { "├── " }, name);
+            let child_prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+            child.render_children(&child_prefix, out);
+        }
+
+        for name in &self.files {
+            index += 1;
+            let last = index == total;
+            let _ = writeln!(out, "{}{}{}", prefix, if last { "└── " } else { "├── " }, name);
+        }
+    }
+}
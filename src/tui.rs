@@ -0,0 +1,172 @@
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::io;
+
+use code2xml::stats::Skip;
+use code2xml::ScanEntry;
+
+/// 树中的一行：目录或文件
+struct Row {
+    depth: usize,
+    name: String,
+    /// 目录行为其路径前缀（带结尾的 /），文件行为 None
+    dir: Option<String>,
+    /// 文件行在 files 中的下标
+    file: usize,
+}
+
+struct App {
+    files: Vec<String>,
+    checked: Vec<bool>,
+    rows: Vec<Row>,
+    state: ListState,
+}
+
+/// 显示文件树供手动勾选，初始勾选状态为自动规则的结果。
+/// 确认后返回选中的显示路径，取消时返回 None
+pub fn select(entries: Vec<ScanEntry>) -> io::Result<Option<Vec<String>>> {
+    let mut app = App::new(entries);
+    let mut terminal = ratatui::init();
+    let result = app.run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+impl App {
+    fn new(mut entries: Vec<ScanEntry>) -> App {
+        // 被名称规则忽略的文件不会被遍历到，勾选也无效，不列出
+        entries.retain(|e| e.skipped != Some(Skip::Ignored));
+        entries.sort_by(|a, b| a.display.cmp(&b.display));
+        let files: Vec<String> = entries.iter().map(|e| e.display.clone()).collect();
+        let checked = entries.iter().map(ScanEntry::included).collect();
+
+        // 按排序后的路径展开成树：遇到新的上级目录时先插入目录行
+        let mut rows = Vec::new();
+        let mut open: Vec<&str> = Vec::new();
+        for (index, path) in files.iter().enumerate() {
+            let parts: Vec<&str> = path.split('/').collect();
+            let dirs = &parts[..parts.len() - 1];
+            let common = open.iter().zip(dirs).take_while(|(a, b)| a == b).count();
+            open.truncate(common);
+            for (depth, dir) in dirs.iter().enumerate().skip(common) {
+                open.push(dir);
+                rows.push(Row {
+                    depth,
+                    name: format!("{}/", dir),
+                    dir: Some(format!("{}/", dirs[..=depth].join("/"))),
+                    file: 0,
+                });
+            }
+            rows.push(Row { depth: dirs.len(), name: parts[parts.len() - 1].to_string(), dir: None, file: index });
+        }
+
+        let mut state = ListState::default();
+        state.select((!rows.is_empty()).then_some(0));
+        App { files, checked, rows, state }
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<Option<Vec<String>>> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            let Event::Key(key) = event::read()? else { continue };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            let page = terminal.size()?.height.saturating_sub(4).max(1) as isize;
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(None),
+                KeyCode::Enter => return Ok(Some(self.selected())),
+                KeyCode::Up | KeyCode::Char('k') => self.move_by(-1),
+                KeyCode::Down | KeyCode::Char('j') => self.move_by(1),
+                KeyCode::PageUp => self.move_by(-page),
+                KeyCode::PageDown => self.move_by(page),
+                KeyCode::Home => self.state.select_first(),
+                KeyCode::End => self.state.select(Some(self.rows.len().saturating_sub(1))),
+                KeyCode::Char(' ') => self.toggle_current(),
+                KeyCode::Char('a') => {
+                    let all = self.checked.iter().all(|c| *c);
+                    self.checked.iter_mut().for_each(|c| *c = !all);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn move_by(&mut self, delta: isize) {
+        if self.rows.is_empty() {
+            return;
+        }
+        let current = self.state.selected().unwrap_or(0) as isize;
+        let next = (current + delta).clamp(0, self.rows.len() as isize - 1);
+        self.state.select(Some(next as usize));
+    }
+
+    /// 文件行切换自身；目录行在全部选中时全部取消，否则全部选中
+    fn toggle_current(&mut self) {
+        let Some(row) = self.state.selected().and_then(|i| self.rows.get(i)) else { return };
+        match &row.dir {
+            None => self.checked[row.file] = !self.checked[row.file],
+            Some(prefix) => {
+                let indices: Vec<usize> = self.files_under(prefix).collect();
+                let all = indices.iter().all(|&i| self.checked[i]);
+                for i in indices {
+                    self.checked[i] = !all;
+                }
+            }
+        }
+    }
+
+    fn files_under<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = usize> + 'a {
+        self.files.iter().enumerate().filter(move |(_, f)| f.starts_with(prefix)).map(|(i, _)| i)
+    }
+
+    fn selected(&self) -> Vec<String> {
+        self.files.iter().zip(&self.checked).filter(|(_, c)| **c).map(|(f, _)| f.clone()).collect()
+    }
+
+    fn checkbox(&self, row: &Row) -> &'static str {
+        match &row.dir {
+            None if self.checked[row.file] => "[x]",
+            None => "[ ]",
+            Some(prefix) => {
+                let (mut any, mut all) = (false, true);
+                for i in self.files_under(prefix) {
+                    any |= self.checked[i];
+                    all &= self.checked[i];
+                }
+                match (any, all) {
+                    (_, true) => "[x]",
+                    (true, false) => "[-]",
+                    _ => "[ ]",
+                }
+            }
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [header, body, footer] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+
+        let count = self.checked.iter().filter(|c| **c).count();
+        frame.render_widget(Paragraph::new(format!("已选择 {} / {} 个文件", count, self.files.len())), header);
+
+        let items: Vec<ListItem> = self
+            .rows
+            .iter()
+            .map(|row| ListItem::new(Line::from(format!("{}{} {}", "  ".repeat(row.depth), self.checkbox(row), row.name))))
+            .collect();
+        let list = List::new(items)
+            .block(Block::bordered())
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, body, &mut self.state);
+
+        frame.render_widget(
+            Paragraph::new("↑↓ 移动  空格 勾选  a 全选/全不选  Enter 生成  q 取消"),
+            footer,
+        );
+    }
+}
@@ -0,0 +1,49 @@
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::Duration;
+
+/// 连续变更在这段时间内静默后才触发一次重新生成
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// 监视各根目录下的变更，每批相关变更结束后调用一次 regenerate。
+/// is_relevant 用于过滤输出文件自身、被忽略目录等不应触发重建的路径。
+pub fn watch(roots: &[PathBuf], is_relevant: impl Fn(&Path) -> bool, mut regenerate: impl FnMut()) -> io::Result<()> {
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).map_err(to_io)?;
+    for root in roots {
+        watcher.watch(root, RecursiveMode::Recursive).map_err(to_io)?;
+        status!("正在监视 {}", root.display());
+    }
+    status!("按 Ctrl+C 退出");
+
+    let relevant = |event: &notify::Result<notify::Event>| match event {
+        Ok(e) => !matches!(e.kind, EventKind::Access(_)) && e.paths.iter().any(|p| is_relevant(p)),
+        Err(_) => false,
+    };
+
+    loop {
+        // 等待第一个相关事件
+        match rx.recv() {
+            Ok(event) if relevant(&event) => {}
+            Ok(_) => continue,
+            Err(_) => return Ok(()),
+        }
+
+        // 吸收随后的连续事件，直到静默 DEBOUNCE
+        loop {
+            match rx.recv_timeout(DEBOUNCE) {
+                Ok(_) => continue,
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return Ok(()),
+            }
+        }
+
+        regenerate();
+    }
+}
+
+fn to_io(err: notify::Error) -> io::Error {
+    io::Error::other(err.to_string())
+}